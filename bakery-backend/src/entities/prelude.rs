@@ -1,4 +0,0 @@
-//! SeaORM Entity. Generated by sea-orm-codegen
-
-pub use super::bakery::Entity as Bakery;
-pub use super::chef::Entity as Chef;
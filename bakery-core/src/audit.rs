@@ -0,0 +1,172 @@
+//! Records the changes of the audited entities in the `audit_log` table,
+//! called by the `ActiveModelBehavior` of `bakery` and `chef`.
+
+use chrono::SecondsFormat;
+use sea_orm::{
+    entity::prelude::*, sea_query, ActiveModelBehavior, ActiveValue, IntoActiveModel, Iterable,
+    TransactionTrait,
+};
+use serde_json::{json, Map};
+
+use crate::entities::audit_log::{self, Action};
+
+/// The JSON of a column value: `null` for NULL and a timestamp as an RFC 3339 string.
+///
+/// `sea_value_to_json_value` writes dates and times as the SQL literals of
+/// sea-query, quoted and without their fractions of a second, and NULL ones as `"NULL"`.
+fn to_json(value: Option<Value>) -> Json {
+    fn or_null<T>(value: Option<Box<T>>, to_string: impl FnOnce(&T) -> String) -> Json {
+        value.map_or(Json::Null, |value| Json::String(to_string(&value)))
+    }
+
+    match value {
+        None => Json::Null,
+        Some(Value::ChronoDateTimeUtc(time)) => or_null(time, |time| {
+            time.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+        }),
+        Some(Value::ChronoDateTimeLocal(time)) => or_null(time, |time| {
+            time.to_rfc3339_opts(SecondsFormat::AutoSi, false)
+        }),
+        Some(Value::ChronoDateTimeWithTimeZone(time)) => or_null(time, |time| {
+            time.to_rfc3339_opts(SecondsFormat::AutoSi, false)
+        }),
+        // Without a time zone, so only in the ISO 8601 format
+        Some(Value::ChronoDateTime(time)) => {
+            or_null(time, |time| time.format("%Y-%m-%dT%H:%M:%S%.f").to_string())
+        }
+        Some(Value::ChronoDate(date)) => or_null(date, ToString::to_string),
+        Some(Value::ChronoTime(time)) => or_null(time, ToString::to_string),
+        Some(value) => sea_query::sea_value_to_json_value(&value),
+    }
+}
+
+fn change(old: Option<Value>, new: Option<Value>) -> Json {
+    json!({ "old": to_json(old), "new": to_json(new) })
+}
+
+fn primary_key_column<A: ActiveModelTrait>() -> <A::Entity as EntityTrait>::Column {
+    <A::Entity as EntityTrait>::PrimaryKey::iter()
+        .next()
+        .expect("An entity has a primary key")
+        .into_column()
+}
+
+fn primary_key<A: ActiveModelTrait>(model: &A) -> Result<i32, DbErr> {
+    model
+        .get(primary_key_column::<A>())
+        .into_value()
+        .and_then(|value| <i32 as sea_query::ValueType>::try_from(value).ok())
+        .ok_or_else(|| DbErr::Custom("An audited record must have an integer id.".to_owned()))
+}
+
+async fn record<C, A>(
+    db: &C,
+    model: &A,
+    action: Action,
+    changes: Map<String, Json>,
+) -> Result<(), DbErr>
+where
+    C: ConnectionTrait,
+    A: ActiveModelTrait,
+{
+    audit_log::ActiveModel {
+        entity: ActiveValue::Set(A::Entity::default().table_name().to_owned()),
+        entity_id: ActiveValue::Set(primary_key(model)?),
+        action: ActiveValue::Set(action),
+        changes: ActiveValue::Set(Json::Object(changes)),
+        created_at: ActiveValue::Set(chrono::Utc::now()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Records the insertion of `model`, with all its values as new values
+pub(crate) async fn record_insert<C, A>(db: &C, model: &A) -> Result<(), DbErr>
+where
+    C: ConnectionTrait,
+    A: ActiveModelTrait + Sync,
+{
+    let changes = <A::Entity as EntityTrait>::Column::iter()
+        .map(|column| {
+            let new = model.get(column).into_value();
+            (column.as_str().to_owned(), change(None, new))
+        })
+        .collect();
+
+    record(db, model, Action::Insert, changes).await
+}
+
+/// Records the update of `model`, comparing the values it sets with the stored record,
+/// and returns whether any value changes. Nothing is recorded when none does.
+pub(crate) async fn record_update<C, A>(db: &C, model: &A) -> Result<bool, DbErr>
+where
+    C: ConnectionTrait,
+    A: ActiveModelTrait + Sync,
+    <A::Entity as EntityTrait>::Model: IntoActiveModel<A>,
+{
+    let Some(stored) = A::Entity::find()
+        .filter(primary_key_column::<A>().eq(primary_key(model)?))
+        .one(db)
+        .await?
+    else {
+        return Ok(false);
+    };
+    let stored = stored.into_active_model();
+
+    let changes: Map<_, _> = <A::Entity as EntityTrait>::Column::iter()
+        .filter_map(|column| {
+            let ActiveValue::Set(new) = model.get(column) else {
+                return None;
+            };
+            let old = stored.get(column).into_value();
+            (old.as_ref() != Some(&new))
+                .then(|| (column.as_str().to_owned(), change(old, Some(new))))
+        })
+        .collect();
+    if changes.is_empty() {
+        return Ok(false);
+    }
+
+    record(db, model, Action::Update, changes).await?;
+    Ok(true)
+}
+
+/// Records the deletion of `model`, with its known values as old values
+pub(crate) async fn record_delete<C, A>(db: &C, model: &A) -> Result<(), DbErr>
+where
+    C: ConnectionTrait,
+    A: ActiveModelTrait + Sync,
+{
+    let changes = <A::Entity as EntityTrait>::Column::iter()
+        .filter_map(|column| {
+            let old = model.get(column).into_value()?;
+            Some((column.as_str().to_owned(), change(Some(old), None)))
+        })
+        .collect();
+
+    record(db, model, Action::Delete, changes).await
+}
+
+/// Updates `model` of an audited entity in a transaction with its audit log entry.
+///
+/// The entry is written by `before_save`, before the `UPDATE` runs, so that a plain
+/// `update` outside a transaction keeps it even when the `UPDATE` then fails (e.g. on a
+/// foreign key violation). Here both are rolled back together.
+pub async fn update_audited<A, C>(
+    model: A,
+    db: &C,
+) -> Result<<A::Entity as EntityTrait>::Model, DbErr>
+where
+    A: ActiveModelTrait + ActiveModelBehavior + Send,
+    <A::Entity as EntityTrait>::Model: IntoActiveModel<A>,
+    C: TransactionTrait,
+{
+    let txn = db.begin().await?;
+    let model = model.update(&txn).await?;
+    txn.commit().await?;
+
+    Ok(model)
+}
@@ -0,0 +1,112 @@
+//! The contact details of a chef, checked by the active model of `chef` before they are
+//! saved and by the chef form and the GraphQL mutations.
+
+use std::fmt;
+
+use sea_orm::FromJsonQueryResult;
+use serde::{Deserialize, Serialize};
+
+/// How to reach a chef, stored as JSON in the `contact_details` column
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+#[cfg_attr(
+    feature = "graphql",
+    derive(async_graphql::SimpleObject, async_graphql::InputObject)
+)]
+#[cfg_attr(feature = "graphql", graphql(input_name = "ContactDetailsInput"))]
+pub struct ContactDetails {
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub address: Option<String>,
+}
+
+/// A field of [`ContactDetails`] with an invalid value
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidContactDetails {
+    /// `email`, `phone` or `address`
+    pub field: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for InvalidContactDetails {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid {}: {}", self.field, self.message)
+    }
+}
+
+impl std::error::Error for InvalidContactDetails {}
+
+impl ContactDetails {
+    const MAX_ADDRESS_LEN: usize = 200;
+
+    pub fn is_empty(&self) -> bool {
+        self.email.is_none() && self.phone.is_none() && self.address.is_none()
+    }
+
+    /// Checks that the email has the form `name@domain`, that the phone number has
+    /// 6 to 20 digits and that the address is not too long. The fields that are set
+    /// must not be blank.
+    pub fn validate(&self) -> Result<(), InvalidContactDetails> {
+        let invalid = |field, message: &str| {
+            Err(InvalidContactDetails {
+                field,
+                message: message.to_owned(),
+            })
+        };
+
+        for (field, value) in [
+            ("email", &self.email),
+            ("phone", &self.phone),
+            ("address", &self.address),
+        ] {
+            if value
+                .as_deref()
+                .is_some_and(|value| value.trim().is_empty())
+            {
+                return invalid(field, "Please enter a value or leave it out.");
+            }
+        }
+
+        if let Some(email) = &self.email {
+            let valid = email.split_once('@').is_some_and(|(name, domain)| {
+                !name.is_empty()
+                    && domain.contains('.')
+                    && !domain.starts_with('.')
+                    && !domain.ends_with('.')
+                    && !domain.contains('@')
+            });
+            if !valid || email.chars().any(char::is_whitespace) {
+                return invalid(
+                    "email",
+                    "Please enter an email address like name@example.com.",
+                );
+            }
+        }
+
+        if let Some(phone) = &self.phone {
+            let digits = phone.chars().filter(char::is_ascii_digit).count();
+            let valid = phone
+                .chars()
+                .all(|c| c.is_ascii_digit() || " +-().".contains(c));
+            if !valid || !(6..=20).contains(&digits) {
+                return invalid(
+                    "phone",
+                    "Please enter a phone number of 6 to 20 digits, e.g. +44 20 7946 0958.",
+                );
+            }
+        }
+
+        if let Some(address) = &self.address {
+            if address.chars().count() > Self::MAX_ADDRESS_LEN {
+                return invalid(
+                    "address",
+                    &format!(
+                        "The address must have at most {} characters.",
+                        Self::MAX_ADDRESS_LEN
+                    ),
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,137 @@
+//! The `chef` entity, with the relation to its bakery following the policy of the
+//! `on-bakery-delete-*` features.
+
+use sea_orm::{entity::prelude::*, ActiveValue};
+use serde::{Deserialize, Serialize};
+
+use crate::{audit, ContactDetails};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+#[cfg_attr(feature = "graphql", graphql(complex, name = "Chef"))]
+#[sea_orm(table_name = "chef")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub name: String,
+    pub contact_details: Option<ContactDetails>,
+    #[cfg_attr(feature = "on-bakery-delete-set-null", sea_orm(nullable))]
+    pub bakery_id: BakeryId,
+    pub created_at: DateTimeUtc,
+    pub updated_at: DateTimeUtc,
+    /// When the record is soft deleted, see [`crate::SoftDelete`]
+    pub deleted_at: Option<DateTimeUtc>,
+}
+
+/// The type of `bakery_id`, which is only nullable with the `on-bakery-delete-set-null`
+/// feature: the chefs are then kept without a bakery when it is deleted
+#[cfg(feature = "on-bakery-delete-set-null")]
+pub type BakeryId = Option<i32>;
+#[cfg(not(feature = "on-bakery-delete-set-null"))]
+pub type BakeryId = i32;
+
+// The feature of this crate must be the one choosing the policy of the migration
+const _: () = assert!(
+    cfg!(feature = "on-bakery-delete-set-null")
+        == matches!(
+            migration::OnBakeryDelete::CHOSEN,
+            migration::OnBakeryDelete::SetNull
+        )
+);
+
+/// The `bakery_id` of a chef working at the bakery `id`
+#[cfg(feature = "on-bakery-delete-set-null")]
+pub fn bakery_id(id: i32) -> BakeryId {
+    Some(id)
+}
+
+/// The `bakery_id` of a chef working at the bakery `id`
+#[cfg(not(feature = "on-bakery-delete-set-null"))]
+pub fn bakery_id(id: i32) -> BakeryId {
+    id
+}
+
+impl Model {
+    /// The id of the bakery of the chef, `None` once it is deleted
+    #[cfg(feature = "on-bakery-delete-set-null")]
+    pub fn works_at(&self) -> Option<i32> {
+        self.bakery_id
+    }
+
+    /// The id of the bakery of the chef, which cannot be deleted without the chef
+    #[cfg(not(feature = "on-bakery-delete-set-null"))]
+    pub fn works_at(&self) -> Option<i32> {
+        Some(self.bakery_id)
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {
+    Bakery,
+}
+
+/// Written by hand, as the `on_delete` action of `Bakery` is the policy chosen by the
+/// `on-bakery-delete-*` features, see [`migration::OnBakeryDelete`]
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        match self {
+            Self::Bakery => Entity::belongs_to(super::bakery::Entity)
+                .from(Column::BakeryId)
+                .to(super::bakery::Column::Id)
+                .on_update(ForeignKeyAction::Cascade)
+                .on_delete(migration::OnBakeryDelete::CHOSEN.action())
+                .into(),
+        }
+    }
+}
+
+impl Related<super::bakery::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Bakery.def()
+    }
+}
+
+/// Validates the contact details, keeps the timestamps up to date and records every change
+/// in the audit log, see [`crate::audit`]
+#[async_trait::async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    async fn before_save<C>(mut self, db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        if let ActiveValue::Set(Some(contact_details)) = &self.contact_details {
+            contact_details
+                .validate()
+                .map_err(|err| DbErr::Custom(err.to_string()))?;
+        }
+
+        let now = chrono::Utc::now();
+        if insert {
+            if self.created_at.is_not_set() {
+                self.created_at = ActiveValue::Set(now);
+            }
+            self.updated_at = ActiveValue::Set(now);
+        } else if audit::record_update(db, &self).await? {
+            self.updated_at = ActiveValue::Set(now);
+        }
+        Ok(self)
+    }
+
+    async fn after_save<C>(model: Model, db: &C, insert: bool) -> Result<Model, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        if insert {
+            audit::record_insert(db, &ActiveModel::from(model.clone())).await?;
+        }
+        Ok(model)
+    }
+
+    async fn after_delete<C>(self, db: &C) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        audit::record_delete(db, &self).await?;
+        Ok(self)
+    }
+}
@@ -2,5 +2,7 @@
 
 pub mod prelude;
 
+pub mod audit_log;
 pub mod bakery;
 pub mod chef;
+pub mod user;
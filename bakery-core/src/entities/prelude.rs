@@ -1,4 +1,6 @@
 //! SeaORM Entity. Generated by sea-orm-codegen
 
+pub use super::audit_log::Entity as AuditLog;
 pub use super::bakery::Entity as Bakery;
 pub use super::chef::Entity as Chef;
+pub use super::user::Entity as User;
@@ -0,0 +1,35 @@
+//! The `user` entity, the accounts allowed to sign in to `rocket-example` with their role.
+
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// What a user is allowed to do
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "String(Some(16))")]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// Can only browse the bakeries and chefs
+    #[sea_orm(string_value = "viewer")]
+    Viewer,
+    /// Can also create, edit and delete them
+    #[sea_orm(string_value = "manager")]
+    Manager,
+}
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "user")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    #[sea_orm(unique)]
+    pub username: String,
+    /// The argon2 hash of the password, in the PHC string format
+    #[serde(skip_serializing)]
+    pub password_hash: String,
+    pub role: Role,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
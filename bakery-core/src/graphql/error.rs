@@ -0,0 +1,104 @@
+//! Errors of the resolvers, told apart by clients through the `code` extension of the
+//! GraphQL error (e.g. `{ "message": "...", "extensions": { "code": "NOT_FOUND" } }`).
+
+use async_graphql::ErrorExtensions;
+use sea_orm::{DbErr, SqlErr};
+
+#[derive(Clone, Debug)]
+pub enum ResolverError {
+    /// The requested record does not exist, or is soft deleted
+    NotFound(String),
+    /// An argument is invalid, `field` names it (e.g. `input.name`)
+    BadUserInput { message: String, field: String },
+    /// The record refers to another one which does not exist, `field` names the
+    /// argument holding the reference when it is known
+    ForeignKeyViolation {
+        message: String,
+        field: Option<String>,
+    },
+    /// Anything else, whose cause is logged rather than sent to the client
+    Internal,
+}
+
+impl ResolverError {
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::NotFound(message.into())
+    }
+
+    pub fn bad_user_input(field: &str, message: impl Into<String>) -> Self {
+        Self::BadUserInput {
+            message: message.into(),
+            field: field.to_owned(),
+        }
+    }
+
+    pub fn foreign_key_violation(field: &str, message: impl Into<String>) -> Self {
+        Self::ForeignKeyViolation {
+            message: message.into(),
+            field: Some(field.to_owned()),
+        }
+    }
+
+    fn code(&self) -> &'static str {
+        match self {
+            Self::NotFound(_) => "NOT_FOUND",
+            Self::BadUserInput { .. } => "BAD_USER_INPUT",
+            Self::ForeignKeyViolation { .. } => "FOREIGN_KEY_VIOLATION",
+            Self::Internal => "INTERNAL",
+        }
+    }
+
+    fn message(&self) -> &str {
+        match self {
+            Self::NotFound(message)
+            | Self::BadUserInput { message, .. }
+            | Self::ForeignKeyViolation { message, .. } => message,
+            Self::Internal => "Something went wrong on our side, please try again later.",
+        }
+    }
+
+    fn field(&self) -> Option<&str> {
+        match self {
+            Self::BadUserInput { field, .. } => Some(field),
+            Self::ForeignKeyViolation { field, .. } => field.as_deref(),
+            Self::NotFound(_) | Self::Internal => None,
+        }
+    }
+}
+
+impl From<DbErr> for ResolverError {
+    fn from(err: DbErr) -> Self {
+        match err.sql_err() {
+            Some(SqlErr::ForeignKeyConstraintViolation(cause)) => Self::ForeignKeyViolation {
+                message: format!("The change is refused by the related records ({cause})."),
+                field: None,
+            },
+            _ => match err {
+                DbErr::RecordNotFound(message) => Self::NotFound(message),
+                err => {
+                    log::error!("Database error: {}", err);
+                    Self::Internal
+                }
+            },
+        }
+    }
+}
+
+/// Errors of async-graphql itself, such as missing schema data, are bugs of the server
+impl From<async_graphql::Error> for ResolverError {
+    fn from(err: async_graphql::Error) -> Self {
+        log::error!("GraphQL error: {}", err.message);
+        Self::Internal
+    }
+}
+
+impl From<ResolverError> for async_graphql::Error {
+    fn from(err: ResolverError) -> Self {
+        async_graphql::Error::new(err.message()).extend_with(|_, extensions| {
+            extensions.set("code", err.code());
+            if let Some(field) = err.field() {
+                extensions.set("field", field);
+            }
+        })
+    }
+}
@@ -0,0 +1,127 @@
+mod error;
+
+use std::{
+    collections::HashMap,
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+
+use async_graphql::{dataloader::*, ComplexObject, Context};
+use sea_orm::*;
+
+use crate::{
+    entities::{prelude::*, *},
+    SoftDelete,
+};
+
+pub use error::ResolverError;
+
+/// Batches the queries of the relations between bakeries and chefs, so that a nested
+/// GraphQL query issues one query per level instead of one per parent.
+///
+/// It must be in the schema data as a `DataLoader<RelationLoader>`.
+pub struct RelationLoader {
+    db: Arc<DatabaseConnection>,
+}
+
+impl RelationLoader {
+    pub fn new(db: Arc<DatabaseConnection>) -> Self {
+        Self { db }
+    }
+}
+
+/// Key of the chefs of a bakery, compared by the bakery id. It holds the whole bakery
+/// as SeaORM's `LoaderTrait` loads the related models of models.
+#[derive(Clone)]
+pub struct ChefsOf(bakery::Model);
+
+impl PartialEq for ChefsOf {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.id == other.0.id
+    }
+}
+
+impl Eq for ChefsOf {}
+
+impl Hash for ChefsOf {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.id.hash(state);
+    }
+}
+
+/// Key of the bakery of a chef, compared by the `bakery_id` of the chef
+#[derive(Clone)]
+pub struct BakeryOf(chef::Model);
+
+impl PartialEq for BakeryOf {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.bakery_id == other.0.bakery_id
+    }
+}
+
+impl Eq for BakeryOf {}
+
+impl Hash for BakeryOf {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.bakery_id.hash(state);
+    }
+}
+
+#[async_graphql::async_trait::async_trait]
+impl Loader<ChefsOf> for RelationLoader {
+    type Value = Vec<chef::Model>;
+    type Error = ResolverError;
+
+    async fn load(&self, keys: &[ChefsOf]) -> Result<HashMap<ChefsOf, Self::Value>, Self::Error> {
+        let bakeries: Vec<_> = keys.iter().map(|key| key.0.clone()).collect();
+        let chefs = bakeries
+            .load_many(Chef::find_active(), self.db.as_ref())
+            .await?;
+
+        Ok(keys.iter().cloned().zip(chefs).collect())
+    }
+}
+
+#[async_graphql::async_trait::async_trait]
+impl Loader<BakeryOf> for RelationLoader {
+    type Value = bakery::Model;
+    type Error = ResolverError;
+
+    async fn load(&self, keys: &[BakeryOf]) -> Result<HashMap<BakeryOf, Self::Value>, Self::Error> {
+        let chefs: Vec<_> = keys.iter().map(|key| key.0.clone()).collect();
+        let bakeries = chefs
+            .load_one(Bakery::find_active(), self.db.as_ref())
+            .await?;
+
+        Ok(keys
+            .iter()
+            .cloned()
+            .zip(bakeries)
+            .filter_map(|(key, bakery)| Some((key, bakery?)))
+            .collect())
+    }
+}
+
+#[ComplexObject]
+impl bakery::Model {
+    async fn chefs(&self, ctx: &Context<'_>) -> Result<Vec<chef::Model>, ResolverError> {
+        let loader = ctx.data::<DataLoader<RelationLoader>>()?;
+
+        Ok(loader
+            .load_one(ChefsOf(self.clone()))
+            .await?
+            .unwrap_or_default())
+    }
+}
+
+#[ComplexObject]
+impl chef::Model {
+    async fn bakery(&self, ctx: &Context<'_>) -> Result<Option<bakery::Model>, ResolverError> {
+        if self.works_at().is_none() {
+            return Ok(None);
+        }
+        let loader = ctx.data::<DataLoader<RelationLoader>>()?;
+
+        loader.load_one(BakeryOf(self.clone())).await
+    }
+}
@@ -0,0 +1,27 @@
+//! The bakery domain shared by all the tutorials: the SeaORM entities,
+//! the migrations of the schema and the database set up, with its Rocket
+//! configuration and fairing behind the `rocket` feature.
+
+mod audit;
+mod contact_details;
+pub mod entities;
+#[cfg(feature = "graphql")]
+mod graphql;
+#[cfg(feature = "rocket")]
+mod rocket_setup;
+mod setup;
+mod soft_delete;
+mod validation;
+
+pub use audit::update_audited;
+pub use contact_details::{ContactDetails, InvalidContactDetails};
+#[cfg(feature = "graphql")]
+pub use graphql::{RelationLoader, ResolverError};
+pub use migration::{self, Migrator};
+#[cfg(feature = "rocket")]
+pub use rocket_setup::{db_config, run_migrations};
+#[cfg(feature = "sqlite")]
+pub use setup::set_up_memory_db;
+pub use setup::{set_up_db, DbConfig};
+pub use soft_delete::SoftDelete;
+pub use validation::{validate_profit_margin, InvalidProfitMargin, PROFIT_MARGIN_RANGE};
@@ -0,0 +1,53 @@
+//! The database set up of the Rocket tutorials, only built with the `rocket` feature.
+
+use migration::MigratorTrait;
+use rocket::{
+    fairing::AdHoc,
+    figment::{providers::Env, Figment},
+    Build, Rocket,
+};
+use sea_orm::*;
+
+use crate::{DbConfig, Migrator};
+
+/// Reads the database settings from the `[default.database]` table of `Rocket.toml`,
+/// overridden in every profile by `DB_*` environment variables (e.g. `DB_URL`, `DB_NAME`).
+pub fn db_config(figment: &Figment) -> Result<DbConfig, Box<rocket::figment::Error>> {
+    figment
+        .focus("database")
+        .merge(Env::prefixed("DB_").global())
+        .extract()
+        .map_err(Box::new)
+}
+
+/// Creates a fairing that applies the pending migrations of [`Migrator`] on ignite,
+/// then hands the connection over to `manage`. A failed migration aborts the launch.
+pub fn run_migrations<F>(db: DatabaseConnection, manage: F) -> AdHoc
+where
+    F: FnOnce(Rocket<Build>, DatabaseConnection) -> Rocket<Build> + Send + 'static,
+{
+    AdHoc::try_on_ignite("Migrations", |rocket| async move {
+        let result = async {
+            let pending = Migrator::get_pending_migrations(&db).await?;
+            Migrator::up(&db, None).await?;
+            Ok::<_, DbErr>(pending)
+        }
+        .await;
+
+        match result {
+            Ok(pending) => {
+                if pending.is_empty() {
+                    rocket::info!("Database schema is up to date.");
+                }
+                for migration in pending {
+                    rocket::info!("Applied migration {}", migration.name());
+                }
+                Ok(manage(rocket, db))
+            }
+            Err(err) => {
+                rocket::error!("Failed to run migrations: {}", err);
+                Err(rocket)
+            }
+        }
+    })
+}
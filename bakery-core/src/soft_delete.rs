@@ -0,0 +1,135 @@
+//! Soft deletion of the bakeries and chefs: a deleted record only gets a `deleted_at`
+//! timestamp, and can be restored.
+
+use sea_orm::{entity::prelude::*, ActiveModelBehavior, IntoActiveModel, Select, TransactionTrait};
+
+use crate::entities::{bakery, chef};
+
+/// An entity whose records are soft deleted.
+///
+/// SeaORM has no default scope, so listings must use [`SoftDelete::find_active`]
+/// instead of `find` to hide the soft deleted records.
+#[async_trait::async_trait]
+pub trait SoftDelete: EntityTrait
+where
+    Self::Model: IntoActiveModel<Self::ActiveModel>,
+{
+    type ActiveModel: ActiveModelTrait<Entity = Self> + ActiveModelBehavior + Send;
+
+    /// The nullable `deleted_at` column
+    fn deleted_at() -> Self::Column;
+
+    /// Selects the records which are not soft deleted
+    fn find_active() -> Select<Self> {
+        Self::find().filter(Self::deleted_at().is_null())
+    }
+
+    /// Selects the record with the primary key `values`, unless it is soft deleted
+    fn find_active_by_id<T>(values: T) -> Select<Self>
+    where
+        T: Into<<Self::PrimaryKey as PrimaryKeyTrait>::ValueType>,
+    {
+        Self::find_by_id(values).filter(Self::deleted_at().is_null())
+    }
+
+    /// Selects the soft deleted records
+    fn find_deleted() -> Select<Self> {
+        Self::find().filter(Self::deleted_at().is_not_null())
+    }
+
+    /// Soft deletes `model` now
+    async fn soft_delete<C>(model: Self::Model, db: &C) -> Result<Self::Model, DbErr>
+    where
+        C: ConnectionTrait + TransactionTrait,
+    {
+        set_deleted_at::<Self, _>(model, Some(chrono::Utc::now()), db).await
+    }
+
+    /// Restores the soft deleted `model`
+    async fn restore<C>(model: Self::Model, db: &C) -> Result<Self::Model, DbErr>
+    where
+        C: ConnectionTrait + TransactionTrait,
+    {
+        set_deleted_at::<Self, _>(model, None, db).await
+    }
+}
+
+/// Sets the `deleted_at` column through the active model, so that the change is audited
+async fn set_deleted_at<E, C>(
+    model: E::Model,
+    deleted_at: Option<DateTimeUtc>,
+    db: &C,
+) -> Result<E::Model, DbErr>
+where
+    E: SoftDelete,
+    E::Model: IntoActiveModel<E::ActiveModel>,
+    C: TransactionTrait,
+{
+    let mut model = model.into_active_model();
+    model.set(E::deleted_at(), deleted_at.into());
+    crate::update_audited(model, db).await
+}
+
+/// Soft deleting a bakery also soft deletes the chefs working there at the same time,
+/// and restoring it restores them.
+#[async_trait::async_trait]
+impl SoftDelete for bakery::Entity {
+    type ActiveModel = bakery::ActiveModel;
+
+    fn deleted_at() -> bakery::Column {
+        bakery::Column::DeletedAt
+    }
+
+    async fn soft_delete<C>(model: bakery::Model, db: &C) -> Result<bakery::Model, DbErr>
+    where
+        C: ConnectionTrait + TransactionTrait,
+    {
+        let deleted_at = Some(chrono::Utc::now());
+        let txn = db.begin().await?;
+
+        let chefs = model
+            .find_related(chef::Entity)
+            .filter(chef::Column::DeletedAt.is_null())
+            .all(&txn)
+            .await?;
+        for chef in chefs {
+            set_deleted_at::<chef::Entity, _>(chef, deleted_at, &txn).await?;
+        }
+        let model = set_deleted_at::<Self, _>(model, deleted_at, &txn).await?;
+
+        txn.commit().await?;
+        Ok(model)
+    }
+
+    async fn restore<C>(model: bakery::Model, db: &C) -> Result<bakery::Model, DbErr>
+    where
+        C: ConnectionTrait + TransactionTrait,
+    {
+        let Some(deleted_at) = model.deleted_at else {
+            return Ok(model);
+        };
+        let txn = db.begin().await?;
+
+        let chefs = model
+            .find_related(chef::Entity)
+            .filter(chef::Column::DeletedAt.eq(deleted_at))
+            .all(&txn)
+            .await?;
+        for chef in chefs {
+            set_deleted_at::<chef::Entity, _>(chef, None, &txn).await?;
+        }
+        let model = set_deleted_at::<Self, _>(model, None, &txn).await?;
+
+        txn.commit().await?;
+        Ok(model)
+    }
+}
+
+#[async_trait::async_trait]
+impl SoftDelete for chef::Entity {
+    type ActiveModel = chef::ActiveModel;
+
+    fn deleted_at() -> chef::Column {
+        chef::Column::DeletedAt
+    }
+}
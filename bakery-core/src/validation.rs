@@ -0,0 +1,34 @@
+//! Rules of the bakery values, checked by the active model of `bakery` before it is
+//! saved and shared by the forms, the REST API and the GraphQL mutations.
+
+use std::{fmt, ops::RangeInclusive};
+
+/// The profit margins a bakery may have, in percent
+pub const PROFIT_MARGIN_RANGE: RangeInclusive<f64> = -100.0..=100.0;
+
+/// A profit margin out of [`PROFIT_MARGIN_RANGE`], or not a number
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InvalidProfitMargin;
+
+impl fmt::Display for InvalidProfitMargin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "The profit margin must be between {} and {}.",
+            PROFIT_MARGIN_RANGE.start(),
+            PROFIT_MARGIN_RANGE.end()
+        )
+    }
+}
+
+impl std::error::Error for InvalidProfitMargin {}
+
+/// Checks that the profit margin is within [`PROFIT_MARGIN_RANGE`], which also refuses
+/// NaN and the infinities
+pub fn validate_profit_margin(profit_margin: f64) -> Result<(), InvalidProfitMargin> {
+    if PROFIT_MARGIN_RANGE.contains(&profit_margin) {
+        Ok(())
+    } else {
+        Err(InvalidProfitMargin)
+    }
+}
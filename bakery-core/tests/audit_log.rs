@@ -0,0 +1,88 @@
+//! The changes recorded in the audit log, on an in-memory SQLite database.
+
+use bakery_core::{
+    entities::{audit_log::Action, prelude::*, *},
+    set_up_memory_db, update_audited, SoftDelete,
+};
+use sea_orm::{prelude::Json, *};
+use serde_json::json;
+
+/// The changes of the last entry of the audit log, which must be an update of `bakery`
+async fn last_update(db: &DatabaseConnection, bakery: &bakery::Model) -> Result<Json, DbErr> {
+    let entry = AuditLog::find()
+        .order_by_desc(audit_log::Column::Id)
+        .one(db)
+        .await?
+        .unwrap();
+    assert_eq!(entry.entity, "bakery");
+    assert_eq!(entry.entity_id, bakery.id);
+    assert_eq!(entry.action, Action::Update);
+
+    Ok(entry.changes)
+}
+
+#[async_std::test]
+async fn deleted_at_is_recorded_as_null_or_rfc3339() -> Result<(), DbErr> {
+    let db = set_up_memory_db().await?;
+
+    let bakery = bakery::ActiveModel {
+        name: ActiveValue::Set("Happy Bakery".to_owned()),
+        profit_margin: ActiveValue::Set(0.0),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await?;
+    let inserted = AuditLog::find().one(&db).await?.unwrap();
+    assert_eq!(inserted.action, Action::Insert);
+    assert_eq!(
+        inserted.changes["deleted_at"],
+        json!({ "old": null, "new": null })
+    );
+
+    let bakery = Bakery::soft_delete(bakery, &db).await?;
+    let deleted_at = bakery.deleted_at.unwrap();
+    let changes = last_update(&db, &bakery).await?;
+    assert_eq!(changes["deleted_at"]["old"], Json::Null);
+    // Down to the fraction of a second
+    let new = changes["deleted_at"]["new"].as_str().unwrap();
+    assert_eq!(
+        chrono::DateTime::parse_from_rfc3339(new).unwrap(),
+        deleted_at
+    );
+
+    let bakery = Bakery::restore(bakery, &db).await?;
+    let changes = last_update(&db, &bakery).await?;
+    assert_eq!(changes["deleted_at"]["old"], json!(new));
+    assert_eq!(changes["deleted_at"]["new"], Json::Null);
+
+    Ok(())
+}
+
+#[async_std::test]
+async fn failed_update_is_not_recorded() -> Result<(), DbErr> {
+    let db = set_up_memory_db().await?;
+
+    let bakery = bakery::ActiveModel {
+        name: ActiveValue::Set("Happy Bakery".to_owned()),
+        profit_margin: ActiveValue::Set(0.0),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await?;
+    let chef = chef::ActiveModel {
+        name: ActiveValue::Set("John".to_owned()),
+        bakery_id: ActiveValue::Set(chef::bakery_id(bakery.id)),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await?;
+    let entries = AuditLog::find().count(&db).await?;
+
+    // Refused by the foreign key, after `before_save` has written the entry
+    let mut moved = chef.into_active_model();
+    moved.bakery_id = ActiveValue::Set(chef::bakery_id(bakery.id + 1));
+    assert!(update_audited(moved, &db).await.is_err());
+    assert_eq!(AuditLog::find().count(&db).await?, entries);
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+//! Deleting a bakery with a chef under the policy chosen by the `on-bakery-delete-*`
+//! features, on an in-memory SQLite database.
+
+use bakery_core::{
+    entities::{prelude::*, *},
+    migration::OnBakeryDelete,
+    set_up_memory_db,
+};
+use sea_orm::*;
+
+#[async_std::test]
+async fn deleting_a_bakery_follows_the_policy() -> Result<(), DbErr> {
+    let db = set_up_memory_db().await?;
+
+    let bakery = bakery::ActiveModel {
+        name: ActiveValue::Set("Happy Bakery".to_owned()),
+        profit_margin: ActiveValue::Set(0.0),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await?;
+    let chef = chef::ActiveModel {
+        name: ActiveValue::Set("John".to_owned()),
+        bakery_id: ActiveValue::Set(chef::bakery_id(bakery.id)),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await?;
+
+    let deleted = bakery.clone().delete(&db).await;
+    let chef = Chef::find_by_id(chef.id).one(&db).await?;
+    match OnBakeryDelete::CHOSEN {
+        OnBakeryDelete::Cascade => {
+            deleted?;
+            assert_eq!(chef, None);
+        }
+        OnBakeryDelete::SetNull => {
+            deleted?;
+            let chef = chef.unwrap();
+            assert_eq!(chef.works_at(), None);
+            assert_eq!(chef.name, "John");
+        }
+        OnBakeryDelete::Restrict => {
+            // Not classified by `DbErr::sql_err`, as SQLite reports `RESTRICT` with its own
+            // error code
+            assert!(deleted.is_err());
+            assert!(Bakery::find_by_id(bakery.id).one(&db).await?.is_some());
+            assert_eq!(chef.unwrap().works_at(), Some(bakery.id));
+        }
+    }
+
+    Ok(())
+}
@@ -1,6 +0,0 @@
-//! SeaORM Entity. Generated by sea-orm-codegen
-
-pub mod prelude;
-
-pub mod bakery;
-pub mod chef;
@@ -1,28 +1,41 @@
-mod entities;
-mod migrator;
+mod pagination;
 mod schema;
-mod setup;
+mod subscription;
+
+use std::sync::Arc;
 
 use async_graphql::{
-    http::{playground_source, GraphQLPlaygroundConfig},
-    EmptySubscription, Schema,
+    dataloader::DataLoader,
+    http::{playground_source, GraphQLPlaygroundConfig, WebSocket, WebSocketProtocols, WsMessage},
+    Schema,
 };
 use async_graphql_rocket::*;
-use rocket::{response::content, *};
+use bakery_core::{db_config, run_migrations, set_up_db, RelationLoader};
+use rocket::{
+    futures::{future, SinkExt, StreamExt},
+    request::{FromRequest, Outcome},
+    response::{content, Responder},
+    *,
+};
+use rocket_ws::{frame::CloseFrame, Message};
 use schema::*;
-use sea_orm::DbErr;
-use setup::set_up_db;
+use subscription::{Changes, SubscriptionRoot};
+
+/// How many changes a subscriber can lag behind before missing some
+const CHANGES_CAPACITY: usize = 256;
 
-type SchemaType = Schema<QueryRoot, MutationRoot, EmptySubscription>;
+type SchemaType = Schema<QueryRoot, MutationRoot, SubscriptionRoot>;
 
 #[get("/")]
 fn index() -> String {
     "Hello, bakeries!".to_owned()
 }
 
-#[rocket::get("/graphql")]
+#[rocket::get("/graphql", rank = 2)]
 fn graphql_playground() -> content::RawHtml<String> {
-    content::RawHtml(playground_source(GraphQLPlaygroundConfig::new("/graphql")))
+    content::RawHtml(playground_source(
+        GraphQLPlaygroundConfig::new("/graphql").subscription_endpoint("/graphql"),
+    ))
 }
 
 #[rocket::post("/graphql", data = "<request>", format = "application/json")]
@@ -32,20 +45,112 @@ async fn graphql_request(schema: &State<SchemaType>, request: GraphQLRequest) ->
     request.execute(schema).await
 }
 
+/// The GraphQL over WebSocket protocol asked for by the client in the
+/// `Sec-WebSocket-Protocol` header, either `graphql-transport-ws` or the legacy `graphql-ws`
+struct GraphQLProtocol(WebSocketProtocols);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for GraphQLProtocol {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, ()> {
+        let protocol = req
+            .headers()
+            .get("Sec-WebSocket-Protocol")
+            .flat_map(|header| header.split(','))
+            .find_map(|protocol| protocol.trim().parse().ok());
+
+        match protocol {
+            Some(protocol) => Outcome::Success(GraphQLProtocol(protocol)),
+            None => Outcome::Error((http::Status::BadRequest, ())),
+        }
+    }
+}
+
+/// A WebSocket upgrade answering with the chosen subprotocol, which `rocket_ws` leaves out
+struct WithProtocol<R>(R, WebSocketProtocols);
+
+impl<'r, 'o: 'r, R: Responder<'r, 'o>> Responder<'r, 'o> for WithProtocol<R> {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'o> {
+        let mut response = self.0.respond_to(req)?;
+        response.set_raw_header("Sec-WebSocket-Protocol", self.1.sec_websocket_protocol());
+
+        Ok(response)
+    }
+}
+
+/// Serves the subscriptions (as well as queries and mutations) over WebSocket
+#[rocket::get("/graphql", rank = 1)]
+fn graphql_ws(
+    schema: &State<SchemaType>,
+    ws: rocket_ws::WebSocket,
+    protocol: GraphQLProtocol,
+) -> WithProtocol<rocket_ws::Channel<'static>> {
+    let schema = schema.inner().clone();
+    let GraphQLProtocol(protocol) = protocol;
+
+    let channel = ws.channel(move |stream| {
+        Box::pin(async move {
+            let (mut sink, stream) = stream.split();
+            let requests = stream
+                .take_while(|message| future::ready(message.is_ok()))
+                .filter_map(|message| match message {
+                    Ok(message @ (Message::Text(_) | Message::Binary(_))) => {
+                        future::ready(Some(message.into_data()))
+                    }
+                    _ => future::ready(None),
+                });
+            let mut responses = WebSocket::new(schema, requests, protocol);
+
+            while let Some(response) = responses.next().await {
+                match response {
+                    WsMessage::Text(text) => sink.send(Message::Text(text)).await?,
+                    WsMessage::Close(code, reason) => {
+                        let frame = CloseFrame {
+                            code: code.into(),
+                            reason: reason.into(),
+                        };
+                        sink.send(Message::Close(Some(frame))).await?;
+                        break;
+                    }
+                }
+            }
+
+            Ok(())
+        })
+    });
+
+    WithProtocol(channel, protocol)
+}
+
 #[launch]
 async fn rocket() -> _ {
-    let db = match set_up_db().await {
+    let db_config = match db_config(&Config::figment()) {
+        Ok(config) => config,
+        Err(err) => panic!("{}", err),
+    };
+    let db = match set_up_db(&db_config).await {
         Ok(db) => db,
         Err(err) => panic!("{}", err),
     };
 
-    let schema = Schema::build(QueryRoot, MutationRoot, EmptySubscription)
-        .data(db)
-        .finish();
-
     rocket::build()
-        .manage(schema)
-        .mount("/", routes![index, graphql_playground, graphql_request])
+        .attach(run_migrations(db, |rocket, db| {
+            // Shared by the resolvers and the loader of the relations
+            let db = Arc::new(db);
+            let loader = DataLoader::new(RelationLoader::new(db.clone()), rocket::tokio::spawn);
+            let schema = Schema::build(QueryRoot, MutationRoot, SubscriptionRoot)
+                .data(db)
+                .data(loader)
+                .data(Changes::new(CHANGES_CAPACITY))
+                .finish();
+
+            rocket.manage(schema)
+        }))
+        .mount(
+            "/",
+            routes![index, graphql_playground, graphql_request, graphql_ws],
+        )
         .register("/", catchers![not_found])
 }
 
@@ -53,29 +158,3 @@ async fn rocket() -> _ {
 pub fn not_found(req: &Request<'_>) -> String {
     format!("{} not found.", req.uri())
 }
-
-#[derive(Responder)]
-#[response(status = 500, content_type = "json")]
-struct ErrorResponder {
-    message: String,
-}
-
-impl From<DbErr> for ErrorResponder {
-    fn from(err: DbErr) -> ErrorResponder {
-        ErrorResponder {
-            message: err.to_string(),
-        }
-    }
-}
-
-impl From<String> for ErrorResponder {
-    fn from(string: String) -> ErrorResponder {
-        ErrorResponder { message: string }
-    }
-}
-
-impl From<&str> for ErrorResponder {
-    fn from(str: &str) -> ErrorResponder {
-        str.to_owned().into()
-    }
-}
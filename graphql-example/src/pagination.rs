@@ -0,0 +1,468 @@
+//! Relay cursor connections of the list queries, paged with SeaORM's `cursor_by`.
+
+use async_graphql::{
+    connection::{Connection, CursorType, Edge, OpaqueCursor},
+    Enum, InputObject, InputType, OutputType,
+};
+use bakery_core::{entities::*, ResolverError};
+use sea_orm::{prelude::DateTimeUtc, sea_query::ValueTuple, *};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// The page size when neither `first` nor `last` is given
+const DEFAULT_PAGE_SIZE: i32 = 20;
+pub(crate) const MAX_PAGE_SIZE: i32 = 100;
+
+/// A page of records ordered by `F`
+pub(crate) type Page<F, M> = Connection<OpaqueCursor<Position<F>>, M>;
+
+#[derive(Enum, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum OrderDirection {
+    #[default]
+    Asc,
+    Desc,
+}
+
+#[derive(InputObject, Default)]
+#[graphql(
+    concrete(name = "BakeryOrder", params(BakeryOrderField)),
+    concrete(name = "ChefOrder", params(ChefOrderField))
+)]
+pub(crate) struct OrderBy<F: InputType + Default> {
+    #[graphql(default)]
+    field: F,
+    #[graphql(default)]
+    direction: OrderDirection,
+}
+
+#[derive(Enum, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum BakeryOrderField {
+    #[default]
+    Id,
+    Name,
+    ProfitMargin,
+    CreatedAt,
+}
+
+#[derive(Enum, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum ChefOrderField {
+    #[default]
+    Id,
+    Name,
+    CreatedAt,
+}
+
+/// An inclusive range, open on the side left out
+#[derive(InputObject)]
+pub(crate) struct FloatRange {
+    min: Option<f64>,
+    max: Option<f64>,
+}
+
+#[derive(InputObject, Default)]
+pub(crate) struct BakeryFilter {
+    /// Part of the name, case sensitive or not depending on the database
+    name_contains: Option<String>,
+    profit_margin: Option<FloatRange>,
+}
+
+#[derive(InputObject, Default)]
+pub(crate) struct ChefFilter {
+    /// Part of the name, case sensitive or not depending on the database
+    name_contains: Option<String>,
+    bakery_id: Option<i32>,
+}
+
+impl BakeryFilter {
+    pub(crate) fn apply(self, select: Select<bakery::Entity>) -> Select<bakery::Entity> {
+        let (min, max) = self
+            .profit_margin
+            .map_or((None, None), |range| (range.min, range.max));
+
+        select
+            .apply_if(self.name_contains, |select, name| {
+                select.filter(bakery::Column::Name.contains(name))
+            })
+            .apply_if(min, |select, min| {
+                select.filter(bakery::Column::ProfitMargin.gte(min))
+            })
+            .apply_if(max, |select, max| {
+                select.filter(bakery::Column::ProfitMargin.lte(max))
+            })
+    }
+}
+
+impl ChefFilter {
+    pub(crate) fn apply(self, select: Select<chef::Entity>) -> Select<chef::Entity> {
+        select
+            .apply_if(self.name_contains, |select, name| {
+                select.filter(chef::Column::Name.contains(name))
+            })
+            .apply_if(self.bakery_id, |select, bakery_id| {
+                select.filter(chef::Column::BakeryId.eq(bakery_id))
+            })
+    }
+}
+
+/// The value of the column ordered by
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) enum Key {
+    Text(String),
+    Float(f64),
+    Time(DateTimeUtc),
+}
+
+impl From<Key> for Value {
+    fn from(key: Key) -> Value {
+        match key {
+            Key::Text(text) => text.into(),
+            Key::Float(float) => float.into(),
+            Key::Time(time) => time.into(),
+        }
+    }
+}
+
+/// What the opaque cursor of an edge holds: the order it was read in, the value of the
+/// column ordered by and the id, which breaks the ties
+#[derive(Serialize, Deserialize)]
+pub(crate) struct Position<F> {
+    field: F,
+    key: Option<Key>,
+    id: i32,
+}
+
+/// A column that the records of an entity can be ordered by
+pub(crate) trait OrderField:
+    InputType + Copy + Default + PartialEq + Serialize + DeserializeOwned + Send + Sync
+{
+    type Entity: EntityTrait<Model = Self::Model>;
+    type Model: ModelTrait<Entity = Self::Entity> + OutputType + FromQueryResult + Sync;
+
+    /// The id column, ordered by after the column of the field
+    const ID: <Self::Entity as EntityTrait>::Column;
+
+    /// The column of the field, `None` for the id itself
+    fn column(self) -> Option<<Self::Entity as EntityTrait>::Column>;
+
+    fn position(self, model: &Self::Model) -> Position<Self>;
+}
+
+impl OrderField for BakeryOrderField {
+    type Entity = bakery::Entity;
+    type Model = bakery::Model;
+
+    const ID: bakery::Column = bakery::Column::Id;
+
+    fn column(self) -> Option<bakery::Column> {
+        match self {
+            Self::Id => None,
+            Self::Name => Some(bakery::Column::Name),
+            Self::ProfitMargin => Some(bakery::Column::ProfitMargin),
+            Self::CreatedAt => Some(bakery::Column::CreatedAt),
+        }
+    }
+
+    fn position(self, bakery: &bakery::Model) -> Position<Self> {
+        let key = match self {
+            Self::Id => None,
+            Self::Name => Some(Key::Text(bakery.name.clone())),
+            Self::ProfitMargin => Some(Key::Float(bakery.profit_margin)),
+            Self::CreatedAt => Some(Key::Time(bakery.created_at)),
+        };
+
+        Position {
+            field: self,
+            key,
+            id: bakery.id,
+        }
+    }
+}
+
+impl OrderField for ChefOrderField {
+    type Entity = chef::Entity;
+    type Model = chef::Model;
+
+    const ID: chef::Column = chef::Column::Id;
+
+    fn column(self) -> Option<chef::Column> {
+        match self {
+            Self::Id => None,
+            Self::Name => Some(chef::Column::Name),
+            Self::CreatedAt => Some(chef::Column::CreatedAt),
+        }
+    }
+
+    fn position(self, chef: &chef::Model) -> Position<Self> {
+        let key = match self {
+            Self::Id => None,
+            Self::Name => Some(Key::Text(chef.name.clone())),
+            Self::CreatedAt => Some(Key::Time(chef.created_at)),
+        };
+
+        Position {
+            field: self,
+            key,
+            id: chef.id,
+        }
+    }
+}
+
+/// The values to page from, checking that the cursor was read in the same order
+fn bound<F: OrderField>(
+    argument: &str,
+    field: F,
+    cursor: &str,
+) -> Result<ValueTuple, ResolverError> {
+    let OpaqueCursor(position) = OpaqueCursor::<Position<F>>::decode_cursor(cursor)
+        .map_err(|_| ResolverError::bad_user_input(argument, "The cursor is invalid."))?;
+
+    match (field.column(), position.key) {
+        _ if position.field != field => Err(ResolverError::bad_user_input(
+            argument,
+            "The cursor was read in another order.",
+        )),
+        (None, _) => Ok(ValueTuple::One(position.id.into())),
+        (Some(_), Some(key)) => Ok(ValueTuple::Two(key.into(), position.id.into())),
+        (Some(_), None) => Err(ResolverError::bad_user_input(
+            argument,
+            "The cursor is invalid.",
+        )),
+    }
+}
+
+/// Reads the page of `select` given by the arguments of a Relay connection.
+///
+/// One more record than asked for is read to tell whether there is a next page (or a
+/// previous one with `last`). The other side only tells whether there is a cursor.
+pub(crate) async fn paginate<F: OrderField>(
+    db: &DatabaseConnection,
+    select: Select<F::Entity>,
+    order_by: OrderBy<F>,
+    after: Option<String>,
+    before: Option<String>,
+    first: Option<i32>,
+    last: Option<i32>,
+) -> Result<Page<F, F::Model>, ResolverError> {
+    let OrderBy { field, direction } = order_by;
+    let (argument, limit) = match (first, last) {
+        (Some(_), Some(_)) => {
+            return Err(ResolverError::bad_user_input(
+                "last",
+                "`first` and `last` cannot be given together.",
+            ))
+        }
+        (None, Some(last)) => ("last", last),
+        (first, None) => ("first", first.unwrap_or(DEFAULT_PAGE_SIZE)),
+    };
+    if !(0..=MAX_PAGE_SIZE).contains(&limit) {
+        return Err(ResolverError::bad_user_input(
+            argument,
+            format!("From 0 to {MAX_PAGE_SIZE} records can be read at once."),
+        ));
+    }
+    let limit = limit as usize;
+
+    let mut cursor = match field.column() {
+        Some(column) => select.cursor_by((column, F::ID)),
+        None => select.cursor_by(F::ID),
+    };
+    if direction == OrderDirection::Desc {
+        cursor.desc();
+    }
+    if let Some(after) = &after {
+        cursor.after(bound("after", field, after)?);
+    }
+    if let Some(before) = &before {
+        cursor.before(bound("before", field, before)?);
+    }
+    if last.is_some() {
+        cursor.last(limit as u64 + 1);
+    } else {
+        cursor.first(limit as u64 + 1);
+    }
+
+    let mut models = cursor.all(db).await?;
+    let has_more = models.len() > limit;
+    let (has_previous_page, has_next_page) = if last.is_some() {
+        if has_more {
+            models.remove(0);
+        }
+        (has_more, before.is_some())
+    } else {
+        models.truncate(limit);
+        (after.is_some(), has_more)
+    };
+
+    let mut page = Connection::new(has_previous_page, has_next_page);
+    page.edges.extend(
+        models
+            .into_iter()
+            .map(|model| Edge::new(OpaqueCursor(field.position(&model)), model)),
+    );
+
+    Ok(page)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cursor(field: BakeryOrderField, key: Option<Key>, id: i32) -> String {
+        OpaqueCursor(Position { field, key, id }).encode_cursor()
+    }
+
+    #[test]
+    fn cursor_keeps_the_exact_float() {
+        // Spread over the whole range of the doubles, with all the bits of the mantissa
+        let mut bits = 0x3fb9_9999_9999_999a_u64;
+        for _ in 0..10_000 {
+            bits = bits
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            let float = f64::from_bits(bits >> 1);
+            if !float.is_finite() {
+                continue;
+            }
+
+            let after = cursor(BakeryOrderField::ProfitMargin, Some(Key::Float(float)), 7);
+            let ValueTuple::Two(Value::Double(Some(key)), id) =
+                bound("after", BakeryOrderField::ProfitMargin, &after).unwrap()
+            else {
+                panic!("The bound of a float is a double and an id");
+            };
+            assert_eq!(key.to_bits(), float.to_bits());
+            assert_eq!(id, Value::Int(Some(7)));
+        }
+    }
+
+    #[test]
+    fn cursor_of_another_order_is_refused() {
+        let after = cursor(BakeryOrderField::Name, Some(Key::Text("A".to_owned())), 1);
+        assert!(matches!(
+            bound("after", BakeryOrderField::ProfitMargin, &after),
+            Err(ResolverError::BadUserInput { field, .. }) if field == "after"
+        ));
+        assert!(bound("after", BakeryOrderField::Id, "not a cursor").is_err());
+        let after = cursor(BakeryOrderField::Name, None, 1);
+        assert!(bound("after", BakeryOrderField::Name, &after).is_err());
+    }
+
+    #[cfg(feature = "sqlite")]
+    mod sqlite {
+        use bakery_core::set_up_memory_db;
+
+        use super::super::*;
+
+        /// The profit margins of the bakeries, with ties, by increasing id
+        const PROFIT_MARGINS: [f64; 7] = [0.3, 0.1, 0.2, 0.1, 0.2, 0.3, 0.2];
+
+        async fn bakeries() -> DatabaseConnection {
+            let db = set_up_memory_db().await.unwrap();
+
+            for (i, profit_margin) in PROFIT_MARGINS.into_iter().enumerate() {
+                bakery::ActiveModel {
+                    name: ActiveValue::Set(format!("Bakery {i}")),
+                    profit_margin: ActiveValue::Set(profit_margin),
+                    ..Default::default()
+                }
+                .insert(&db)
+                .await
+                .unwrap();
+            }
+
+            db
+        }
+
+        /// The ids by profit margin then id, in `direction`
+        fn expected(direction: OrderDirection) -> Vec<i32> {
+            let mut ids: Vec<i32> = (1..=PROFIT_MARGINS.len() as i32).collect();
+            ids.sort_by(|a, b| {
+                let margin = |id: &i32| PROFIT_MARGINS[*id as usize - 1];
+                margin(a).total_cmp(&margin(b)).then(a.cmp(b))
+            });
+            if direction == OrderDirection::Desc {
+                ids.reverse();
+            }
+            ids
+        }
+
+        fn order_by(direction: OrderDirection) -> OrderBy<BakeryOrderField> {
+            OrderBy {
+                field: BakeryOrderField::ProfitMargin,
+                direction,
+            }
+        }
+
+        /// Reads all the pages of 2 bakeries, forward with `first` and `after` or backward
+        /// with `last` and `before`, and returns the ids in order
+        async fn read_pages(
+            db: &DatabaseConnection,
+            direction: OrderDirection,
+            backward: bool,
+        ) -> Vec<i32> {
+            let mut ids = Vec::new();
+            let mut cursor = None;
+            loop {
+                let (after, before, first, last) = if backward {
+                    (None, cursor, None, Some(2))
+                } else {
+                    (cursor, None, Some(2), None)
+                };
+                let page = paginate(
+                    db,
+                    bakery::Entity::find(),
+                    order_by(direction),
+                    after,
+                    before,
+                    first,
+                    last,
+                )
+                .await
+                .unwrap();
+
+                let page_ids: Vec<i32> = page.edges.iter().map(|edge| edge.node.id).collect();
+                let more = if backward {
+                    ids.splice(0..0, page_ids);
+                    cursor = page.edges.first().map(|edge| edge.cursor.encode_cursor());
+                    page.has_previous_page
+                } else {
+                    ids.extend(page_ids);
+                    cursor = page.edges.last().map(|edge| edge.cursor.encode_cursor());
+                    page.has_next_page
+                };
+                if !more {
+                    return ids;
+                }
+            }
+        }
+
+        #[rocket::async_test]
+        async fn pages_break_the_ties_by_id() {
+            let db = bakeries().await;
+
+            for direction in [OrderDirection::Asc, OrderDirection::Desc] {
+                assert_eq!(read_pages(&db, direction, false).await, expected(direction));
+                assert_eq!(read_pages(&db, direction, true).await, expected(direction));
+            }
+        }
+
+        #[rocket::async_test]
+        async fn first_and_last_cannot_be_given_together() {
+            let db = bakeries().await;
+
+            let page = paginate(
+                &db,
+                bakery::Entity::find(),
+                order_by(OrderDirection::Asc),
+                None,
+                None,
+                Some(2),
+                Some(2),
+            )
+            .await;
+            assert!(matches!(
+                page,
+                Err(ResolverError::BadUserInput { field, .. }) if field == "last"
+            ));
+        }
+    }
+}
@@ -1,7 +1,16 @@
-use async_graphql::{ComplexObject, Context, Object};
+use std::sync::Arc;
+
+use async_graphql::{Context, InputObject, MaybeUndefined, Object};
+use bakery_core::{
+    entities::{prelude::*, *},
+    update_audited, ContactDetails, ResolverError, SoftDelete,
+};
 use sea_orm::*;
 
-use crate::entities::{prelude::*, *};
+use crate::{
+    pagination::*,
+    subscription::{Changes, MutationType},
+};
 
 pub(crate) struct QueryRoot;
 pub(crate) struct MutationRoot;
@@ -12,66 +21,243 @@ impl QueryRoot {
         "Hello GraphQL".to_owned()
     }
 
-    async fn bakeries(&self, ctx: &Context<'_>) -> Result<Vec<bakery::Model>, DbErr> {
-        let db = ctx.data::<DatabaseConnection>().unwrap();
+    #[allow(clippy::too_many_arguments)]
+    async fn bakeries(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<BakeryFilter>,
+        #[graphql(default)] order_by: OrderBy<BakeryOrderField>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> Result<Page<BakeryOrderField, bakery::Model>, ResolverError> {
+        let db = ctx.data::<Arc<DatabaseConnection>>()?.as_ref();
 
-        Bakery::find().all(db).await
+        let select = filter.unwrap_or_default().apply(Bakery::find_active());
+
+        paginate(db, select, order_by, after, before, first, last).await
     }
 
-    async fn bakery(&self, ctx: &Context<'_>, id: i32) -> Result<Option<bakery::Model>, DbErr> {
-        let db = ctx.data::<DatabaseConnection>().unwrap();
+    async fn bakery(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+    ) -> Result<Option<bakery::Model>, ResolverError> {
+        let db = ctx.data::<Arc<DatabaseConnection>>()?.as_ref();
 
-        Bakery::find_by_id(id).one(db).await
+        Ok(Bakery::find_active_by_id(id).one(db).await?)
     }
 
-    async fn chefs(&self, ctx: &Context<'_>) -> Result<Vec<chef::Model>, DbErr> {
-        let db = ctx.data::<DatabaseConnection>().unwrap();
+    #[allow(clippy::too_many_arguments)]
+    async fn chefs(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<ChefFilter>,
+        #[graphql(default)] order_by: OrderBy<ChefOrderField>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> Result<Page<ChefOrderField, chef::Model>, ResolverError> {
+        let db = ctx.data::<Arc<DatabaseConnection>>()?.as_ref();
+
+        let select = filter.unwrap_or_default().apply(Chef::find_active());
 
-        Chef::find().all(db).await
+        paginate(db, select, order_by, after, before, first, last).await
     }
 
-    async fn chef(&self, ctx: &Context<'_>, id: i32) -> Result<Option<chef::Model>, DbErr> {
-        let db = ctx.data::<DatabaseConnection>().unwrap();
+    async fn chef(&self, ctx: &Context<'_>, id: i32) -> Result<Option<chef::Model>, ResolverError> {
+        let db = ctx.data::<Arc<DatabaseConnection>>()?.as_ref();
 
-        Chef::find_by_id(id).one(db).await
+        Ok(Chef::find_active_by_id(id).one(db).await?)
     }
+
+    /// The audit log, newest first, optionally only of one `entity` (e.g. `"bakery"`)
+    /// or one of its records, at most 100 entries
+    async fn audit_logs(
+        &self,
+        ctx: &Context<'_>,
+        entity: Option<String>,
+        entity_id: Option<i32>,
+        #[graphql(default = 100)] limit: u64,
+    ) -> Result<Vec<audit_log::Model>, ResolverError> {
+        let db = ctx.data::<Arc<DatabaseConnection>>()?.as_ref();
+        if limit > MAX_PAGE_SIZE as u64 {
+            return Err(ResolverError::bad_user_input(
+                "limit",
+                format!("From 0 to {MAX_PAGE_SIZE} records can be read at once."),
+            ));
+        }
+
+        Ok(AuditLog::find()
+            .apply_if(entity, |query, entity| {
+                query.filter(audit_log::Column::Entity.eq(entity))
+            })
+            .apply_if(entity_id, |query, entity_id| {
+                query.filter(audit_log::Column::EntityId.eq(entity_id))
+            })
+            .order_by_desc(audit_log::Column::Id)
+            .limit(limit)
+            .all(db)
+            .await?)
+    }
+}
+
+/// Changes of `updateBakery`, where the fields left out are not changed
+#[derive(InputObject)]
+pub(crate) struct UpdateBakeryInput {
+    name: Option<String>,
+    profit_margin: Option<f64>,
 }
 
-#[ComplexObject]
-impl bakery::Model {
-    async fn chefs(&self, ctx: &Context<'_>) -> Result<Vec<chef::Model>, DbErr> {
-        let db = ctx.data::<DatabaseConnection>().unwrap();
+/// Changes of `updateChef`, where the fields left out are not changed and a `null`
+/// `contactDetails` removes them
+#[derive(InputObject)]
+pub(crate) struct UpdateChefInput {
+    name: Option<String>,
+    bakery_id: Option<i32>,
+    contact_details: MaybeUndefined<ContactDetails>,
+}
 
-        self.find_related(Chef).all(db).await
+/// `Set` to the given value, or `NotSet` when it is left out
+fn set_if<T: Into<Value>>(value: Option<T>) -> ActiveValue<T> {
+    value.map_or(ActiveValue::NotSet, ActiveValue::Set)
+}
+
+fn validate_name(field: &str, name: &str) -> Result<(), ResolverError> {
+    if name.trim().is_empty() {
+        return Err(ResolverError::bad_user_input(
+            field,
+            "The name must not be empty.",
+        ));
     }
+    Ok(())
+}
+
+fn validate_profit_margin(field: &str, profit_margin: f64) -> Result<(), ResolverError> {
+    bakery_core::validate_profit_margin(profit_margin)
+        .map_err(|err| ResolverError::bad_user_input(field, err.to_string()))
 }
 
-#[ComplexObject]
-impl chef::Model {
-    async fn bakery(&self, ctx: &Context<'_>) -> Result<bakery::Model, DbErr> {
-        let db = ctx.data::<DatabaseConnection>().unwrap();
+fn validate_contact_details(
+    field: &str,
+    contact_details: &ContactDetails,
+) -> Result<(), ResolverError> {
+    contact_details.validate().map_err(|err| {
+        ResolverError::bad_user_input(&format!("{field}.{}", err.field), err.message)
+    })
+}
+
+async fn find_bakery(db: &DatabaseConnection, id: i32) -> Result<bakery::Model, ResolverError> {
+    Bakery::find_active_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| ResolverError::not_found(format!("No bakery with id {id} is found.")))
+}
 
-        self.find_related(Bakery).one(db).await.map(|b| b.unwrap())
+/// Checks that the bakery `id` given in the argument `field` exists and is not deleted
+async fn check_bakery_id(
+    db: &DatabaseConnection,
+    field: &str,
+    id: i32,
+) -> Result<(), ResolverError> {
+    if Bakery::find_active_by_id(id).one(db).await?.is_none() {
+        return Err(ResolverError::foreign_key_violation(
+            field,
+            format!("No bakery with id {id} is found."),
+        ));
     }
+    Ok(())
+}
+
+async fn find_chef(db: &DatabaseConnection, id: i32) -> Result<chef::Model, ResolverError> {
+    Chef::find_active_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| ResolverError::not_found(format!("No chef with id {id} is found.")))
 }
 
 #[Object]
 impl MutationRoot {
-    async fn add_bakery(&self, ctx: &Context<'_>, name: String) -> Result<bakery::Model, DbErr> {
-        let db = ctx.data::<DatabaseConnection>().unwrap();
+    async fn add_bakery(
+        &self,
+        ctx: &Context<'_>,
+        name: String,
+        #[graphql(default)] profit_margin: f64,
+    ) -> Result<bakery::Model, ResolverError> {
+        let db = ctx.data::<Arc<DatabaseConnection>>()?.as_ref();
+        let changes = ctx.data::<Changes>()?;
 
-        let res = Bakery::insert(bakery::ActiveModel {
+        validate_name("name", &name)?;
+        validate_profit_margin("profitMargin", profit_margin)?;
+
+        // Inserted through the active model, so that the insertion is audited
+        let bakery = bakery::ActiveModel {
             name: ActiveValue::Set(name),
-            profit_margin: ActiveValue::Set(0.0),
+            profit_margin: ActiveValue::Set(profit_margin),
             ..Default::default()
-        })
-        .exec(db)
+        }
+        .insert(db)
         .await?;
+        changes.publish_bakery(MutationType::Created, bakery.clone());
 
-        Bakery::find_by_id(res.last_insert_id)
-            .one(db)
-            .await
-            .map(|b| b.unwrap())
+        Ok(bakery)
+    }
+
+    async fn update_bakery(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        input: UpdateBakeryInput,
+    ) -> Result<bakery::Model, ResolverError> {
+        let db = ctx.data::<Arc<DatabaseConnection>>()?.as_ref();
+        let changes = ctx.data::<Changes>()?;
+
+        if let Some(name) = &input.name {
+            validate_name("input.name", name)?;
+        }
+        if let Some(profit_margin) = input.profit_margin {
+            validate_profit_margin("input.profitMargin", profit_margin)?;
+        }
+        let stored = find_bakery(db, id).await?;
+
+        let bakery = bakery::ActiveModel {
+            id: ActiveValue::Unchanged(id),
+            name: set_if(input.name),
+            profit_margin: set_if(input.profit_margin),
+            ..Default::default()
+        };
+        if !bakery.is_changed() {
+            return Ok(stored);
+        }
+        let bakery = update_audited(bakery, db).await?;
+        changes.publish_bakery(MutationType::Updated, bakery.clone());
+
+        Ok(bakery)
+    }
+
+    /// Soft deletes a bakery together with its chefs
+    async fn delete_bakery(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+    ) -> Result<bakery::Model, ResolverError> {
+        let db = ctx.data::<Arc<DatabaseConnection>>()?.as_ref();
+        let changes = ctx.data::<Changes>()?;
+
+        let bakery = Bakery::soft_delete(find_bakery(db, id).await?, db).await?;
+        let chefs = bakery
+            .find_related(Chef)
+            .filter(chef::Column::DeletedAt.eq(bakery.deleted_at))
+            .all(db)
+            .await?;
+        for chef in chefs {
+            changes.publish_chef(MutationType::Deleted, chef);
+        }
+        changes.publish_bakery(MutationType::Deleted, bakery.clone());
+
+        Ok(bakery)
     }
 
     async fn add_chef(
@@ -79,20 +265,99 @@ impl MutationRoot {
         ctx: &Context<'_>,
         name: String,
         bakery_id: i32,
-    ) -> Result<chef::Model, DbErr> {
-        let db = ctx.data::<DatabaseConnection>().unwrap();
+        contact_details: Option<ContactDetails>,
+    ) -> Result<chef::Model, ResolverError> {
+        let db = ctx.data::<Arc<DatabaseConnection>>()?.as_ref();
+        let changes = ctx.data::<Changes>()?;
+
+        validate_name("name", &name)?;
+        if let Some(contact_details) = &contact_details {
+            validate_contact_details("contactDetails", contact_details)?;
+        }
+        check_bakery_id(db, "bakeryId", bakery_id).await?;
 
-        let res = Chef::insert(chef::ActiveModel {
+        let chef = chef::ActiveModel {
             name: ActiveValue::Set(name),
-            bakery_id: ActiveValue::Set(bakery_id),
+            bakery_id: ActiveValue::Set(chef::bakery_id(bakery_id)),
+            contact_details: ActiveValue::Set(contact_details),
             ..Default::default()
-        })
-        .exec(db)
+        }
+        .insert(db)
         .await?;
+        changes.publish_chef(MutationType::Created, chef.clone());
+
+        Ok(chef)
+    }
+
+    async fn update_chef(
+        &self,
+        ctx: &Context<'_>,
+        id: i32,
+        input: UpdateChefInput,
+    ) -> Result<chef::Model, ResolverError> {
+        let db = ctx.data::<Arc<DatabaseConnection>>()?.as_ref();
+        let changes = ctx.data::<Changes>()?;
+
+        if let Some(name) = &input.name {
+            validate_name("input.name", name)?;
+        }
+        if let MaybeUndefined::Value(contact_details) = &input.contact_details {
+            validate_contact_details("input.contactDetails", contact_details)?;
+        }
+        let stored = find_chef(db, id).await?;
+        if let Some(bakery_id) = input.bakery_id {
+            check_bakery_id(db, "input.bakeryId", bakery_id).await?;
+        }
+
+        let chef = chef::ActiveModel {
+            id: ActiveValue::Unchanged(id),
+            name: set_if(input.name),
+            bakery_id: set_if(input.bakery_id.map(chef::bakery_id)),
+            contact_details: match input.contact_details {
+                MaybeUndefined::Undefined => ActiveValue::NotSet,
+                MaybeUndefined::Null => ActiveValue::Set(None),
+                MaybeUndefined::Value(contact_details) => ActiveValue::Set(Some(contact_details)),
+            },
+            ..Default::default()
+        };
+        if !chef.is_changed() {
+            return Ok(stored);
+        }
+        let chef = update_audited(chef, db).await?;
+        changes.publish_chef_update(&stored, chef.clone());
+
+        Ok(chef)
+    }
+
+    /// Soft deletes a chef
+    async fn delete_chef(&self, ctx: &Context<'_>, id: i32) -> Result<chef::Model, ResolverError> {
+        let db = ctx.data::<Arc<DatabaseConnection>>()?.as_ref();
+        let changes = ctx.data::<Changes>()?;
+
+        let chef = Chef::soft_delete(find_chef(db, id).await?, db).await?;
+        changes.publish_chef(MutationType::Deleted, chef.clone());
+
+        Ok(chef)
+    }
+
+    /// Moves a chef to another bakery
+    async fn move_chef(
+        &self,
+        ctx: &Context<'_>,
+        chef_id: i32,
+        bakery_id: i32,
+    ) -> Result<chef::Model, ResolverError> {
+        let db = ctx.data::<Arc<DatabaseConnection>>()?.as_ref();
+        let changes = ctx.data::<Changes>()?;
+
+        let stored = find_chef(db, chef_id).await?;
+        check_bakery_id(db, "bakeryId", bakery_id).await?;
+
+        let mut chef = stored.clone().into_active_model();
+        chef.bakery_id = ActiveValue::Set(chef::bakery_id(bakery_id));
+        let chef = update_audited(chef, db).await?;
+        changes.publish_chef_update(&stored, chef.clone());
 
-        Chef::find_by_id(res.last_insert_id)
-            .one(db)
-            .await
-            .map(|b| b.unwrap())
+        Ok(chef)
     }
 }
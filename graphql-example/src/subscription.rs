@@ -0,0 +1,202 @@
+use async_graphql::{
+    futures_util::{stream, Stream, StreamExt},
+    Context, Enum, SimpleObject, Subscription,
+};
+use bakery_core::{entities::*, ResolverError};
+use rocket::tokio::sync::broadcast::{self, error::RecvError};
+
+pub(crate) struct SubscriptionRoot;
+
+#[derive(Enum, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MutationType {
+    Created,
+    Updated,
+    /// Soft deleted, the record has a `deletedAt`
+    Deleted,
+}
+
+#[derive(SimpleObject, Clone)]
+pub(crate) struct BakeryChanged {
+    mutation_type: MutationType,
+    bakery: bakery::Model,
+}
+
+#[derive(SimpleObject, Clone)]
+pub(crate) struct ChefChanged {
+    mutation_type: MutationType,
+    chef: chef::Model,
+    /// The bakery the chef worked at before the change, when it moved them to another one
+    previous_bakery_id: Option<i32>,
+}
+
+#[derive(Clone)]
+enum Change {
+    Bakery(BakeryChanged),
+    Chef(ChefChanged),
+}
+
+/// The in-process channel through which `MutationRoot` tells the subscriptions about
+/// the bakeries and chefs it has written.
+///
+/// A subscriber lagging more than `capacity` changes behind misses the oldest ones.
+pub(crate) struct Changes(broadcast::Sender<Change>);
+
+impl Changes {
+    pub(crate) fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+
+        Self(sender)
+    }
+
+    pub(crate) fn publish_bakery(&self, mutation_type: MutationType, bakery: bakery::Model) {
+        // Fails only when nobody is subscribed
+        let _ = self.0.send(Change::Bakery(BakeryChanged {
+            mutation_type,
+            bakery,
+        }));
+    }
+
+    pub(crate) fn publish_chef(&self, mutation_type: MutationType, chef: chef::Model) {
+        let _ = self.0.send(Change::Chef(ChefChanged {
+            mutation_type,
+            chef,
+            previous_bakery_id: None,
+        }));
+    }
+
+    /// Publishes the update of `stored` into `chef`, with the bakery the chef left if any
+    pub(crate) fn publish_chef_update(&self, stored: &chef::Model, chef: chef::Model) {
+        let previous_bakery_id = stored.works_at().filter(|&id| Some(id) != chef.works_at());
+        let _ = self.0.send(Change::Chef(ChefChanged {
+            mutation_type: MutationType::Updated,
+            chef,
+            previous_bakery_id,
+        }));
+    }
+
+    fn subscribe(&self) -> impl Stream<Item = Change> {
+        stream::unfold(self.0.subscribe(), |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(change) => return Some((change, receiver)),
+                    Err(RecvError::Lagged(missed)) => {
+                        rocket::warn!("A subscriber missed {} changes.", missed)
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        })
+    }
+}
+
+#[Subscription]
+impl SubscriptionRoot {
+    /// The changes of the bakeries, or only of the bakery `id`
+    async fn bakery_changed(
+        &self,
+        ctx: &Context<'_>,
+        id: Option<i32>,
+    ) -> async_graphql::Result<impl Stream<Item = BakeryChanged>> {
+        let changes = ctx
+            .data::<Changes>()
+            .map_err(ResolverError::from)?
+            .subscribe();
+
+        Ok(changes.filter_map(move |change| async move {
+            match change {
+                Change::Bakery(changed) if id.is_none_or(|id| id == changed.bakery.id) => {
+                    Some(changed)
+                }
+                _ => None,
+            }
+        }))
+    }
+
+    /// The changes of the chefs, or only of the chefs of the bakery `bakery_id`, including
+    /// the chefs moved from it to another bakery
+    async fn chef_changed(
+        &self,
+        ctx: &Context<'_>,
+        bakery_id: Option<i32>,
+    ) -> async_graphql::Result<impl Stream<Item = ChefChanged>> {
+        let changes = ctx
+            .data::<Changes>()
+            .map_err(ResolverError::from)?
+            .subscribe();
+
+        Ok(changes.filter_map(move |change| async move {
+            match change {
+                Change::Chef(changed)
+                    if bakery_id.is_none_or(|id| {
+                        Some(id) == changed.chef.works_at()
+                            || Some(id) == changed.previous_bakery_id
+                    }) =>
+                {
+                    Some(changed)
+                }
+                _ => None,
+            }
+        }))
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use async_graphql::{futures_util::poll, Schema};
+    use bakery_core::set_up_memory_db;
+    use rocket::tokio::time::timeout;
+    use sea_orm::*;
+
+    use super::*;
+    use crate::schema::{MutationRoot, QueryRoot};
+
+    #[rocket::async_test]
+    async fn the_bakery_left_by_a_chef_is_told() {
+        let db = set_up_memory_db().await.unwrap();
+        for name in ["Left", "Joined"] {
+            bakery::ActiveModel {
+                name: ActiveValue::Set(name.to_owned()),
+                profit_margin: ActiveValue::Set(0.0),
+                ..Default::default()
+            }
+            .insert(&db)
+            .await
+            .unwrap();
+        }
+        chef::ActiveModel {
+            name: ActiveValue::Set("John".to_owned()),
+            bakery_id: ActiveValue::Set(chef::bakery_id(1)),
+            ..Default::default()
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        let schema = Schema::build(QueryRoot, MutationRoot, SubscriptionRoot)
+            .data(Arc::new(db))
+            .data(Changes::new(1))
+            .finish();
+
+        let mut changes = schema.execute_stream(
+            "subscription { chefChanged(bakeryId: 1) { previousBakeryId chef { bakeryId } } }",
+        );
+        // Subscribes before the chef is moved
+        assert!(poll!(changes.next()).is_pending());
+        let moved = schema
+            .execute("mutation { moveChef(chefId: 1, bakeryId: 2) { id } }")
+            .await;
+        assert!(moved.errors.is_empty(), "{:?}", moved.errors);
+
+        let changed = timeout(Duration::from_secs(5), changes.next())
+            .await
+            .expect("the subscriber of the bakery left was not told")
+            .unwrap();
+        assert_eq!(
+            changed.data.into_json().unwrap(),
+            serde_json::json!({
+                "chefChanged": { "previousBakeryId": 1, "chef": { "bakeryId": 2 } }
+            })
+        );
+    }
+}
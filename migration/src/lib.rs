@@ -0,0 +1,36 @@
+pub use sea_orm_migration::prelude::*;
+
+#[cfg(all(
+    feature = "on-bakery-delete-cascade",
+    feature = "on-bakery-delete-set-null"
+))]
+compile_error!("Only one of the `on-bakery-delete-*` features can be enabled.");
+
+mod m20220602_000001_create_bakery_table;
+mod m20220602_000002_create_chef_table;
+mod m20261018_000001_create_user_table;
+mod m20261018_000002_create_audit_log_table;
+mod m20261018_000003_add_timestamps;
+mod m20261018_000004_add_deleted_at;
+mod m20261018_000005_set_chef_bakery_on_delete;
+mod m20261018_000006_normalize_contact_details;
+
+pub use m20261018_000005_set_chef_bakery_on_delete::OnBakeryDelete;
+
+pub struct Migrator;
+
+#[async_trait::async_trait]
+impl MigratorTrait for Migrator {
+    fn migrations() -> Vec<Box<dyn MigrationTrait>> {
+        vec![
+            Box::new(m20220602_000001_create_bakery_table::Migration),
+            Box::new(m20220602_000002_create_chef_table::Migration),
+            Box::new(m20261018_000001_create_user_table::Migration),
+            Box::new(m20261018_000002_create_audit_log_table::Migration),
+            Box::new(m20261018_000003_add_timestamps::Migration),
+            Box::new(m20261018_000004_add_deleted_at::Migration),
+            Box::new(m20261018_000005_set_chef_bakery_on_delete::Migration),
+            Box::new(m20261018_000006_normalize_contact_details::Migration),
+        ]
+    }
+}
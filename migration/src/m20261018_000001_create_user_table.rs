@@ -1,12 +1,10 @@
 use sea_orm_migration::prelude::*;
 
-use super::m20220602_000001_create_bakery_table::Bakery;
-
 pub struct Migration;
 
 impl MigrationName for Migration {
     fn name(&self) -> &str {
-        "m_20220602_000002_create_chef_table"
+        "m_20261018_000001_create_user_table"
     }
 }
 
@@ -17,22 +15,26 @@ impl MigrationTrait for Migration {
             .create_table(
                 Table::create()
                     .if_not_exists()
-                    .table(Chef::Table)
+                    .table(User::Table)
                     .col(
-                        ColumnDef::new(Chef::Id)
+                        ColumnDef::new(User::Id)
                             .integer()
                             .not_null()
                             .auto_increment()
                             .primary_key(),
                     )
-                    .col(ColumnDef::new(Chef::Name).string().not_null())
-                    .col(ColumnDef::new(Chef::ContactDetails).json())
-                    .col(ColumnDef::new(Chef::BakeryId).integer().not_null())
-                    .foreign_key(
-                        ForeignKey::create()
-                            .name("fk-chef-bakery_id")
-                            .from(Chef::Table, Chef::BakeryId)
-                            .to(Bakery::Table, Bakery::Id),
+                    .col(
+                        ColumnDef::new(User::Username)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(User::PasswordHash).string().not_null())
+                    .col(
+                        ColumnDef::new(User::Role)
+                            .string_len(16)
+                            .not_null()
+                            .default("viewer"),
                     )
                     .to_owned(),
             )
@@ -41,16 +43,16 @@ impl MigrationTrait for Migration {
 
     async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
         manager
-            .drop_table(Table::drop().table(Chef::Table).to_owned())
+            .drop_table(Table::drop().table(User::Table).to_owned())
             .await
     }
 }
 
 #[derive(Iden)]
-pub enum Chef {
+pub enum User {
     Table,
     Id,
-    Name,
-    ContactDetails,
-    BakeryId,
+    Username,
+    PasswordHash,
+    Role,
 }
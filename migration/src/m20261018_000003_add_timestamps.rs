@@ -0,0 +1,190 @@
+use sea_orm_migration::{prelude::*, sea_orm::DbBackend};
+
+use super::{
+    m20220602_000001_create_bakery_table::Bakery, m20220602_000002_create_chef_table::Chef,
+};
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m_20261018_000003_add_timestamps"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        if manager.get_database_backend() == DbBackend::Sqlite {
+            return rebuild_sqlite_tables(manager).await;
+        }
+
+        for table in [Bakery::Table.into_iden(), Chef::Table.into_iden()] {
+            for column in [Timestamps::CreatedAt, Timestamps::UpdatedAt] {
+                // The existing rows get the time of the migration
+                manager
+                    .alter_table(
+                        Table::alter()
+                            .table(table.clone())
+                            .add_column(&mut timestamp(column))
+                            .to_owned(),
+                    )
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for table in [Bakery::Table.into_iden(), Chef::Table.into_iden()] {
+            for column in [Timestamps::CreatedAt, Timestamps::UpdatedAt] {
+                // One column per statement, as SQLite only supports one change per `ALTER TABLE`
+                manager
+                    .alter_table(
+                        Table::alter()
+                            .table(table.clone())
+                            .drop_column(column)
+                            .to_owned(),
+                    )
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum Timestamps {
+    CreatedAt,
+    UpdatedAt,
+}
+
+/// The tables renamed while SQLite creates them again
+#[derive(Iden, Clone, Copy)]
+enum Renamed {
+    #[iden = "bakery_old"]
+    Bakery,
+    #[iden = "chef_old"]
+    Chef,
+}
+
+/// `created_at` or `updated_at`, the time of the insertion by default
+pub(crate) fn timestamp(column: Timestamps) -> ColumnDef {
+    ColumnDef::new(column)
+        .timestamp_with_time_zone()
+        .not_null()
+        .default(Expr::current_timestamp())
+        .to_owned()
+}
+
+/// SQLite cannot add a column with a non-constant default such as `CURRENT_TIMESTAMP`,
+/// but it can create a table with one. So both tables are created again with the
+/// timestamps and the rows are copied over, getting the time of the migration.
+///
+/// Renaming `bakery` points the foreign key of `chef` to the renamed table, and creating
+/// `chef` again points it back to the new `bakery`, so the renamed tables can be dropped
+/// without deleting any row referred to.
+async fn rebuild_sqlite_tables(manager: &SchemaManager<'_>) -> Result<(), DbErr> {
+    let bakery = Table::create()
+        .table(Bakery::Table)
+        .col(
+            ColumnDef::new(Bakery::Id)
+                .integer()
+                .not_null()
+                .auto_increment()
+                .primary_key(),
+        )
+        .col(ColumnDef::new(Bakery::Name).string().not_null())
+        .col(ColumnDef::new(Bakery::ProfitMargin).double().not_null())
+        .col(&mut timestamp(Timestamps::CreatedAt))
+        .col(&mut timestamp(Timestamps::UpdatedAt))
+        .to_owned();
+    recreate_table(
+        manager,
+        Bakery::Table,
+        Renamed::Bakery,
+        bakery,
+        [
+            Bakery::Id.into_iden(),
+            Bakery::Name.into_iden(),
+            Bakery::ProfitMargin.into_iden(),
+        ],
+    )
+    .await?;
+
+    let chef = Table::create()
+        .table(Chef::Table)
+        .col(
+            ColumnDef::new(Chef::Id)
+                .integer()
+                .not_null()
+                .auto_increment()
+                .primary_key(),
+        )
+        .col(ColumnDef::new(Chef::Name).string().not_null())
+        .col(ColumnDef::new(Chef::ContactDetails).json())
+        .col(ColumnDef::new(Chef::BakeryId).integer().not_null())
+        .col(&mut timestamp(Timestamps::CreatedAt))
+        .col(&mut timestamp(Timestamps::UpdatedAt))
+        .foreign_key(
+            ForeignKey::create()
+                .name("fk-chef-bakery_id")
+                .from(Chef::Table, Chef::BakeryId)
+                .to(Bakery::Table, Bakery::Id),
+        )
+        .to_owned();
+    recreate_table(
+        manager,
+        Chef::Table,
+        Renamed::Chef,
+        chef,
+        [
+            Chef::Id.into_iden(),
+            Chef::Name.into_iden(),
+            Chef::ContactDetails.into_iden(),
+            Chef::BakeryId.into_iden(),
+        ],
+    )
+    .await?;
+
+    for renamed in [Renamed::Chef, Renamed::Bakery] {
+        manager
+            .drop_table(Table::drop().table(renamed).to_owned())
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Renames `table` to `renamed`, creates it again with `create` and copies the `columns`
+/// of the rows over
+async fn recreate_table<const N: usize>(
+    manager: &SchemaManager<'_>,
+    table: impl IntoIden,
+    renamed: Renamed,
+    create: TableCreateStatement,
+    columns: [DynIden; N],
+) -> Result<(), DbErr> {
+    let table = table.into_iden();
+
+    manager
+        .rename_table(
+            Table::rename()
+                .table(table.clone(), renamed.into_iden())
+                .to_owned(),
+        )
+        .await?;
+    manager.create_table(create).await?;
+    manager
+        .exec_stmt(
+            Query::insert()
+                .into_table(table)
+                .columns(columns.clone())
+                .select_from(Query::select().columns(columns).from(renamed).to_owned())
+                .map_err(|err| DbErr::Migration(err.to_string()))?
+                .to_owned(),
+        )
+        .await
+}
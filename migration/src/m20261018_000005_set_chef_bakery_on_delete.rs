@@ -0,0 +1,179 @@
+use sea_orm_migration::{prelude::*, sea_orm::DbBackend};
+
+use super::{
+    m20220602_000001_create_bakery_table::Bakery,
+    m20220602_000002_create_chef_table::Chef,
+    m20261018_000003_add_timestamps::{timestamp, Timestamps},
+    m20261018_000004_add_deleted_at::SoftDelete,
+};
+
+/// What happens to the chefs of a bakery when it is deleted, through the `on_delete`
+/// action of `fk-chef-bakery_id`.
+///
+/// It is chosen when the crate is built, by the `on-bakery-delete-cascade` or
+/// `on-bakery-delete-set-null` feature (`restrict` without either), so that every build
+/// migrates the same schema as the one its `chef` entity describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnBakeryDelete {
+    /// The chefs are deleted with their bakery
+    Cascade,
+    /// The chefs are kept without a bakery, `bakery_id` being nullable
+    SetNull,
+    /// A bakery cannot be deleted while chefs work there
+    Restrict,
+}
+
+impl OnBakeryDelete {
+    /// The policy chosen by the features of the crate
+    pub const CHOSEN: Self = if cfg!(feature = "on-bakery-delete-cascade") {
+        Self::Cascade
+    } else if cfg!(feature = "on-bakery-delete-set-null") {
+        Self::SetNull
+    } else {
+        Self::Restrict
+    };
+
+    pub fn action(self) -> ForeignKeyAction {
+        match self {
+            Self::Cascade => ForeignKeyAction::Cascade,
+            Self::SetNull => ForeignKeyAction::SetNull,
+            Self::Restrict => ForeignKeyAction::Restrict,
+        }
+    }
+}
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m_20261018_000005_set_chef_bakery_on_delete"
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let policy = OnBakeryDelete::CHOSEN;
+
+        let mut foreign_key = foreign_key();
+        foreign_key
+            .on_delete(policy.action())
+            .on_update(ForeignKeyAction::Cascade);
+
+        replace_foreign_key(manager, foreign_key, policy == OnBakeryDelete::SetNull).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Fails if chefs were left without a bakery
+        replace_foreign_key(manager, foreign_key(), false).await
+    }
+}
+
+/// `fk-chef-bakery_id` as created by `m20220602_000002_create_chef_table`
+fn foreign_key() -> ForeignKeyCreateStatement {
+    ForeignKey::create()
+        .name("fk-chef-bakery_id")
+        .from(Chef::Table, Chef::BakeryId)
+        .to(Bakery::Table, Bakery::Id)
+        .to_owned()
+}
+
+async fn replace_foreign_key(
+    manager: &SchemaManager<'_>,
+    mut foreign_key: ForeignKeyCreateStatement,
+    nullable: bool,
+) -> Result<(), DbErr> {
+    let mut bakery_id = ColumnDef::new(Chef::BakeryId);
+    bakery_id.integer();
+    if nullable {
+        bakery_id.null();
+    } else {
+        bakery_id.not_null();
+    }
+
+    if manager.get_database_backend() != DbBackend::Sqlite {
+        manager
+            .drop_foreign_key(
+                ForeignKey::drop()
+                    .name("fk-chef-bakery_id")
+                    .table(Chef::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Chef::Table)
+                    .modify_column(&mut bakery_id)
+                    .to_owned(),
+            )
+            .await?;
+        return manager.create_foreign_key(foreign_key).await;
+    }
+
+    // SQLite can neither alter a foreign key nor the nullability of a column, so the
+    // table is created again with the new definition and the rows are copied over
+    let columns = [
+        Chef::Id.into_iden(),
+        Chef::Name.into_iden(),
+        Chef::ContactDetails.into_iden(),
+        Chef::BakeryId.into_iden(),
+        Timestamps::CreatedAt.into_iden(),
+        Timestamps::UpdatedAt.into_iden(),
+        SoftDelete::DeletedAt.into_iden(),
+    ];
+
+    manager
+        .create_table(
+            Table::create()
+                .table(ChefRebuild::Table)
+                .col(
+                    ColumnDef::new(Chef::Id)
+                        .integer()
+                        .not_null()
+                        .auto_increment()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(Chef::Name).string().not_null())
+                .col(ColumnDef::new(Chef::ContactDetails).json())
+                .col(&mut bakery_id)
+                .col(&mut timestamp(Timestamps::CreatedAt))
+                .col(&mut timestamp(Timestamps::UpdatedAt))
+                .col(ColumnDef::new(SoftDelete::DeletedAt).timestamp_with_time_zone())
+                .foreign_key(foreign_key.from_tbl(ChefRebuild::Table))
+                .to_owned(),
+        )
+        .await?;
+    manager
+        .exec_stmt(
+            Query::insert()
+                .into_table(ChefRebuild::Table)
+                .columns(columns.clone())
+                .select_from(
+                    Query::select()
+                        .columns(columns)
+                        .from(Chef::Table)
+                        .to_owned(),
+                )
+                .map_err(|err| DbErr::Migration(err.to_string()))?
+                .to_owned(),
+        )
+        .await?;
+    manager
+        .drop_table(Table::drop().table(Chef::Table).to_owned())
+        .await?;
+    manager
+        .rename_table(
+            Table::rename()
+                .table(ChefRebuild::Table, Chef::Table)
+                .to_owned(),
+        )
+        .await
+}
+
+/// The chef table being rebuilt in SQLite
+#[derive(Iden)]
+enum ChefRebuild {
+    #[iden = "chef_rebuild"]
+    Table,
+}
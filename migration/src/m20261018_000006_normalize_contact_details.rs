@@ -0,0 +1,78 @@
+use sea_orm_migration::{prelude::*, sea_orm::JsonValue};
+
+use super::m20220602_000002_create_chef_table::Chef;
+
+pub struct Migration;
+
+impl MigrationName for Migration {
+    fn name(&self) -> &str {
+        "m_20261018_000006_normalize_contact_details"
+    }
+}
+
+/// The fields of the `ContactDetails` of `bakery-core`
+const FIELDS: [&str; 3] = ["email", "phone", "address"];
+
+/// Keeps the non-blank `email`, `phone` and `address` of `details`, or nothing if it has none
+fn normalize(details: &JsonValue) -> Option<JsonValue> {
+    let details = details.as_object()?;
+
+    let fields: Vec<_> = FIELDS
+        .into_iter()
+        .filter_map(|field| {
+            let value = match details.get(field)? {
+                JsonValue::String(value) => value.trim().to_owned(),
+                JsonValue::Number(value) => value.to_string(),
+                _ => return None,
+            };
+            (!value.is_empty()).then(|| (field.to_owned(), JsonValue::String(value)))
+        })
+        .collect();
+
+    (!fields.is_empty()).then(|| JsonValue::Object(fields.into_iter().collect()))
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    /// Reshapes the existing contact details of any form into the `ContactDetails` object,
+    /// dropping the other values, so that the rows can be read by the typed entity
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        let rows = db
+            .query_all(
+                manager.get_database_backend().build(
+                    Query::select()
+                        .columns([Chef::Id, Chef::ContactDetails])
+                        .from(Chef::Table)
+                        .and_where(Expr::col(Chef::ContactDetails).is_not_null()),
+                ),
+            )
+            .await?;
+
+        for row in rows {
+            let id: i32 = row.try_get("", &Chef::Id.to_string())?;
+            let details: JsonValue = row.try_get("", &Chef::ContactDetails.to_string())?;
+
+            let normalized = normalize(&details);
+            if normalized.as_ref() != Some(&details) {
+                manager
+                    .exec_stmt(
+                        Query::update()
+                            .table(Chef::Table)
+                            .value(Chef::ContactDetails, normalized)
+                            .and_where(Expr::col(Chef::Id).eq(id))
+                            .to_owned(),
+                    )
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The dropped values are not restored
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Ok(())
+    }
+}
@@ -0,0 +1,111 @@
+//! Administrative routes, which are only mounted when explicitly enabled.
+
+use bakery_core::Migrator;
+use rocket::{
+    fairing::AdHoc,
+    figment::providers::Env,
+    http::Status,
+    request::{FromRequest, Outcome},
+    serde::Deserialize,
+    *,
+};
+use sea_orm::*;
+use sea_orm_migration::MigratorTrait;
+use subtle::ConstantTimeEq;
+
+use super::error::Error;
+
+/// Settings of the administrative routes, read from the `[<profile>.admin]` table of
+/// `Rocket.toml` and `ADMIN_*` environment variables (e.g. `ADMIN_TOKEN`).
+#[derive(Deserialize, Default)]
+#[serde(crate = "rocket::serde", default)]
+pub(super) struct AdminConfig {
+    /// Whether `POST /reset` is mounted, which is only honoured in the debug profile
+    enable_reset: bool,
+    /// The token to be sent as `Authorization: Bearer <token>`
+    token: Option<String>,
+}
+
+/// Request guard of an administrator, who authenticates with the configured token.
+/// Requests without a valid token are forbidden.
+pub(super) struct Admin;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Admin {
+    type Error = &'static str;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let expected = req
+            .rocket()
+            .state::<AdminConfig>()
+            .and_then(|config| config.token.as_deref());
+        let given = req
+            .headers()
+            .get_one("Authorization")
+            .and_then(|value| value.strip_prefix("Bearer "));
+
+        // Compared in constant time so that the token cannot be guessed byte by byte
+        // from the response times, only its length
+        match (expected, given) {
+            (Some(expected), Some(given))
+                if bool::from(given.as_bytes().ct_eq(expected.as_bytes())) =>
+            {
+                Outcome::Success(Admin)
+            }
+            (_, None) => Outcome::Error((Status::Forbidden, "Missing admin token.")),
+            _ => Outcome::Error((Status::Forbidden, "Invalid admin token.")),
+        }
+    }
+}
+
+/// Drops all the tables and applies the migrations again, wiping all data
+#[post("/reset")]
+async fn reset(_admin: Admin, db: &State<DatabaseConnection>) -> Result<(), Error> {
+    let db = db as &DatabaseConnection;
+    Migrator::refresh(db).await?;
+
+    Ok(())
+}
+
+/// Creates a fairing that reads the [`AdminConfig`] and mounts the administrative routes
+/// it enables. `POST /reset` is only mounted in the debug profile, when enabled and with
+/// a non-empty token, so that it answers 404 otherwise.
+pub(super) fn stage() -> AdHoc {
+    AdHoc::try_on_ignite("Admin", |rocket| async move {
+        let figment = rocket.figment().clone();
+        let config = match figment
+            .focus("admin")
+            .merge(Env::prefixed("ADMIN_").global())
+            .extract::<AdminConfig>()
+        {
+            Ok(config) => config,
+            Err(err) => {
+                rocket::error!("Invalid admin configuration: {}", err);
+                return Err(rocket);
+            }
+        };
+
+        let reset_enabled = if !config.enable_reset {
+            false
+        } else if figment.profile() != Config::DEBUG_PROFILE {
+            rocket::warn!(
+                "Ignoring `enable_reset` in the {} profile, POST /reset is disabled.",
+                figment.profile()
+            );
+            false
+        } else if config.token.as_deref().is_none_or(str::is_empty) {
+            rocket::warn!("No admin token is configured, POST /reset is disabled.");
+            false
+        } else {
+            rocket::warn!("POST /reset is enabled, it wipes all data.");
+            true
+        };
+
+        let rocket = rocket.manage(config);
+        Ok(if reset_enabled {
+            rocket.mount("/", routes![reset])
+        } else {
+            rocket
+        })
+    })
+}
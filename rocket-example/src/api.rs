@@ -0,0 +1,177 @@
+//! JSON REST API for bakeries, mounted at `/api`.
+
+use bakery_core::{
+    entities::{prelude::*, *},
+    update_audited, SoftDelete,
+};
+use rocket::{
+    response::status::{Created, NoContent},
+    serde::{json::Json, Deserialize},
+    *,
+};
+use sea_orm::*;
+
+use super::{auth::Manager, error::Error};
+
+type ApiResult<T> = Result<T, Error>;
+
+/// Body of `POST /api/bakeries` and `PUT /api/bakeries/<id>`
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub(super) struct BakeryInput {
+    name: String,
+    #[serde(default)]
+    profit_margin: f64,
+}
+
+/// Body of `PATCH /api/bakeries/<id>`, where every field is optional
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub(super) struct BakeryPatch {
+    name: Option<String>,
+    profit_margin: Option<f64>,
+}
+
+fn validate_name(name: &str) -> ApiResult<()> {
+    if name.trim().is_empty() {
+        return Err(Error::invalid_field(
+            "name",
+            "The name of a bakery must not be empty.",
+        ));
+    }
+    Ok(())
+}
+
+fn validate_profit_margin(profit_margin: f64) -> ApiResult<()> {
+    bakery_core::validate_profit_margin(profit_margin)
+        .map_err(|err| Error::invalid_field("profit_margin", err.to_string()))
+}
+
+fn not_found(id: i32) -> Error {
+    Error::not_found(format!("No bakery with id {id} is found."))
+}
+
+async fn find_bakery(db: &DatabaseConnection, id: i32) -> ApiResult<bakery::Model> {
+    Bakery::find_active_by_id(id)
+        .one(db)
+        .await?
+        .ok_or_else(|| not_found(id))
+}
+
+#[get("/bakeries")]
+async fn list(db: &State<DatabaseConnection>) -> ApiResult<Json<Vec<bakery::Model>>> {
+    let db = db as &DatabaseConnection;
+
+    let bakeries = Bakery::find_active().all(db).await?;
+
+    Ok(Json(bakeries))
+}
+
+#[get("/bakeries/<id>")]
+async fn get(db: &State<DatabaseConnection>, id: i32) -> ApiResult<Json<bakery::Model>> {
+    let db = db as &DatabaseConnection;
+
+    Ok(Json(find_bakery(db, id).await?))
+}
+
+#[post("/bakeries", format = "json", data = "<input>")]
+async fn create(
+    _manager: Manager,
+    db: &State<DatabaseConnection>,
+    input: Json<BakeryInput>,
+) -> ApiResult<Created<Json<bakery::Model>>> {
+    let db = db as &DatabaseConnection;
+    let input = input.into_inner();
+
+    validate_name(&input.name)?;
+    validate_profit_margin(input.profit_margin)?;
+
+    let bakery = bakery::ActiveModel {
+        name: ActiveValue::Set(input.name),
+        profit_margin: ActiveValue::Set(input.profit_margin),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    Ok(Created::new(format!("/api/bakeries/{}", bakery.id)).body(Json(bakery)))
+}
+
+#[put("/bakeries/<id>", format = "json", data = "<input>")]
+async fn replace(
+    _manager: Manager,
+    db: &State<DatabaseConnection>,
+    id: i32,
+    input: Json<BakeryInput>,
+) -> ApiResult<Json<bakery::Model>> {
+    let db = db as &DatabaseConnection;
+    let input = input.into_inner();
+
+    validate_name(&input.name)?;
+    validate_profit_margin(input.profit_margin)?;
+
+    let mut bakery = find_bakery(db, id).await?.into_active_model();
+    bakery.name = ActiveValue::Set(input.name);
+    bakery.profit_margin = ActiveValue::Set(input.profit_margin);
+
+    Ok(Json(update_audited(bakery, db).await?))
+}
+
+#[patch("/bakeries/<id>", format = "json", data = "<input>")]
+async fn update(
+    _manager: Manager,
+    db: &State<DatabaseConnection>,
+    id: i32,
+    input: Json<BakeryPatch>,
+) -> ApiResult<Json<bakery::Model>> {
+    let db = db as &DatabaseConnection;
+    let input = input.into_inner();
+
+    let mut bakery = find_bakery(db, id).await?.into_active_model();
+    if let Some(name) = input.name {
+        validate_name(&name)?;
+        bakery.name = ActiveValue::Set(name);
+    }
+    if let Some(profit_margin) = input.profit_margin {
+        validate_profit_margin(profit_margin)?;
+        bakery.profit_margin = ActiveValue::Set(profit_margin);
+    }
+
+    Ok(Json(update_audited(bakery, db).await?))
+}
+
+#[delete("/bakeries/<id>")]
+async fn delete(
+    _manager: Manager,
+    db: &State<DatabaseConnection>,
+    id: i32,
+) -> ApiResult<NoContent> {
+    let db = db as &DatabaseConnection;
+
+    // Soft deleted with its chefs, see `restore`
+    Bakery::soft_delete(find_bakery(db, id).await?, db).await?;
+
+    Ok(NoContent)
+}
+
+/// Restores a soft deleted bakery and the chefs deleted with it
+#[post("/bakeries/<id>/restore")]
+async fn restore(
+    _manager: Manager,
+    db: &State<DatabaseConnection>,
+    id: i32,
+) -> ApiResult<Json<bakery::Model>> {
+    let db = db as &DatabaseConnection;
+
+    let bakery = Bakery::find_deleted()
+        .filter(bakery::Column::Id.eq(id))
+        .one(db)
+        .await?
+        .ok_or_else(|| Error::not_found(format!("No deleted bakery with id {id} is found.")))?;
+
+    Ok(Json(Bakery::restore(bakery, db).await?))
+}
+
+pub(super) fn routes() -> Vec<Route> {
+    routes![list, get, create, replace, update, delete, restore]
+}
@@ -0,0 +1,52 @@
+//! Read-only web page of the audit log, recorded by `bakery-core`.
+
+use bakery_core::entities::{prelude::*, *};
+use rocket::*;
+use rocket_dyn_templates::Template;
+use sea_orm::*;
+use serde_json::json;
+
+use super::{auth::User, clamp_page, error::Error};
+
+const AUDIT_LOGS_PER_PAGE: u64 = 20;
+
+#[get("/audit?<entity>&<entity_id>&<page>")]
+async fn audit_logs(
+    _user: User,
+    db: &State<DatabaseConnection>,
+    entity: Option<&str>,
+    entity_id: Option<i32>,
+    page: Option<u64>,
+) -> Result<Template, Error> {
+    let db = db as &DatabaseConnection;
+
+    let paginator = AuditLog::find()
+        .apply_if(entity, |query, entity| {
+            query.filter(audit_log::Column::Entity.eq(entity))
+        })
+        .apply_if(entity_id, |query, entity_id| {
+            query.filter(audit_log::Column::EntityId.eq(entity_id))
+        })
+        .order_by_desc(audit_log::Column::Id)
+        .paginate(db, AUDIT_LOGS_PER_PAGE);
+    let num_pages = paginator.num_pages().await?;
+    let page = clamp_page(page, num_pages);
+
+    // Pages are numbered from 1 in the web page, but from 0 by the `Paginator`
+    let entries = paginator.fetch_page(page - 1).await?;
+
+    Ok(Template::render(
+        "audit",
+        json!({
+            "entries": entries,
+            "entity": entity,
+            "entity_id": entity_id,
+            "page": page,
+            "num_pages": num_pages,
+        }),
+    ))
+}
+
+pub(super) fn routes() -> Vec<Route> {
+    routes![audit_logs]
+}
@@ -0,0 +1,329 @@
+//! Accounts of the web pages: sign up, log in and out, and the guards of the logged in users.
+
+use std::collections::HashMap;
+
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use bakery_core::entities::user::{self, Role};
+use rocket::{
+    fairing::AdHoc,
+    figment::providers::Env,
+    form::{Contextual, Form},
+    http::{Cookie, CookieJar, Status},
+    outcome::try_outcome,
+    request::{FlashMessage, FromRequest, Outcome},
+    response::{Flash, Redirect},
+    serde::Deserialize,
+    tokio::task,
+    *,
+};
+use rocket_dyn_templates::Template;
+use sea_orm::*;
+use serde_json::json;
+
+use super::{error::Error, forms::field_errors};
+
+/// Name of the private cookie holding the id of the logged in user
+const SESSION_COOKIE: &str = "user_id";
+
+/// Request guard of a logged in user, answering 401 to anonymous requests
+pub(super) struct User {
+    pub(super) username: String,
+    pub(super) role: Role,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for User {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        // Cached, as a route may ask for both `User` and `Manager`
+        let user = req
+            .local_cache_async(async {
+                let id = req
+                    .cookies()
+                    .get_private(SESSION_COOKIE)?
+                    .value()
+                    .parse::<i32>()
+                    .ok()?;
+                let db = req.rocket().state::<DatabaseConnection>()?;
+                user::Entity::find_by_id(id).one(db).await.ok().flatten()
+            })
+            .await;
+
+        match user {
+            Some(user) => Outcome::Success(User {
+                username: user.username.clone(),
+                role: user.role,
+            }),
+            None => Outcome::Error((Status::Unauthorized, ())),
+        }
+    }
+}
+
+/// Request guard of a logged in manager, answering 403 to viewers
+pub(super) struct Manager;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Manager {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let user = try_outcome!(req.guard::<User>().await);
+
+        match user.role {
+            Role::Manager => Outcome::Success(Manager),
+            Role::Viewer => Outcome::Error((Status::Forbidden, ())),
+        }
+    }
+}
+
+/// Hashes `password` with argon2 and a random salt, away from the async workers
+/// as hashing is slow on purpose
+async fn hash_password(password: String) -> Result<String, Error> {
+    let hash = task::spawn_blocking(move || {
+        let salt = SaltString::generate(&mut OsRng);
+        Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+    })
+    .await;
+
+    match hash {
+        Ok(Ok(hash)) => Ok(hash),
+        Ok(Err(err)) => {
+            rocket::error!("Failed to hash a password: {}", err);
+            Err(Error::Internal)
+        }
+        Err(err) => {
+            rocket::error!("Failed to hash a password: {}", err);
+            Err(Error::Internal)
+        }
+    }
+}
+
+/// Checks `password` against the argon2 `hash` of a user
+async fn verify_password(password: String, hash: String) -> bool {
+    task::spawn_blocking(move || {
+        PasswordHash::new(&hash).is_ok_and(|hash| {
+            Argon2::default()
+                .verify_password(password.as_bytes(), &hash)
+                .is_ok()
+        })
+    })
+    .await
+    .unwrap_or(false)
+}
+
+async fn find_user(db: &DatabaseConnection, username: &str) -> Result<Option<user::Model>, DbErr> {
+    user::Entity::find()
+        .filter(user::Column::Username.eq(username))
+        .one(db)
+        .await
+}
+
+#[derive(FromForm)]
+pub(super) struct LoginForm<'r> {
+    username: &'r str,
+    password: &'r str,
+}
+
+#[derive(FromForm)]
+pub(super) struct SignupForm<'r> {
+    #[field(validate = len(1..=64).or_else(msg!("Please enter a username of at most 64 characters.")))]
+    username: &'r str,
+    #[field(validate = len(8..).or_else(msg!("The password must have at least 8 characters.")))]
+    password: &'r str,
+}
+
+fn login_form(username: &str, error: Option<&str>, flash: Option<FlashMessage<'_>>) -> Template {
+    Template::render(
+        "login",
+        json!({
+            "username": username,
+            "error": error,
+            "flash": flash.map(|f| f.message().to_owned()),
+        }),
+    )
+}
+
+fn signup_form(username: Option<&str>, errors: HashMap<String, String>) -> Template {
+    Template::render(
+        "signup",
+        json!({ "values": { "username": username }, "errors": errors }),
+    )
+}
+
+#[get("/login")]
+fn login(flash: Option<FlashMessage<'_>>) -> Template {
+    login_form("", None, flash)
+}
+
+#[post("/login", data = "<form>")]
+async fn log_in(
+    db: &State<DatabaseConnection>,
+    cookies: &CookieJar<'_>,
+    form: Form<LoginForm<'_>>,
+) -> Result<Result<Flash<Redirect>, (Status, Template)>, Error> {
+    let db = db as &DatabaseConnection;
+
+    let user = match find_user(db, form.username).await? {
+        Some(user)
+            if verify_password(form.password.to_owned(), user.password_hash.clone()).await =>
+        {
+            user
+        }
+        _ => {
+            return Ok(Err((
+                Status::Unauthorized,
+                login_form(form.username, Some("Invalid username or password."), None),
+            )))
+        }
+    };
+
+    cookies.add_private(Cookie::new(SESSION_COOKIE, user.id.to_string()));
+
+    Ok(Ok(Flash::success(
+        Redirect::to("/"),
+        format!("Welcome back, {}!", user.username),
+    )))
+}
+
+#[post("/logout")]
+fn log_out(cookies: &CookieJar<'_>) -> Flash<Redirect> {
+    cookies.remove_private(SESSION_COOKIE);
+
+    Flash::success(Redirect::to(uri!(login)), "You are logged out.")
+}
+
+#[get("/signup")]
+fn signup() -> Template {
+    signup_form(None, HashMap::new())
+}
+
+/// Creates a viewer account and logs it in; managers are configured, see [`stage`]
+#[post("/signup", data = "<form>")]
+async fn sign_up<'r>(
+    db: &State<DatabaseConnection>,
+    cookies: &CookieJar<'_>,
+    form: Form<Contextual<'r, SignupForm<'r>>>,
+) -> Result<Result<Flash<Redirect>, Template>, Error> {
+    let db = db as &DatabaseConnection;
+
+    let (username, password) = match &form.value {
+        Some(value) => (value.username.to_owned(), value.password.to_owned()),
+        None => {
+            let username = form.context.field_value("username");
+            return Ok(Err(signup_form(username, field_errors(&form.context))));
+        }
+    };
+
+    if find_user(db, &username).await?.is_some() {
+        let errors = HashMap::from([(
+            "username".to_owned(),
+            "This username is already taken.".to_owned(),
+        )]);
+        return Ok(Err(signup_form(Some(&username), errors)));
+    }
+
+    let user = user::ActiveModel {
+        username: ActiveValue::Set(username),
+        password_hash: ActiveValue::Set(hash_password(password).await?),
+        role: ActiveValue::Set(Role::Viewer),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    cookies.add_private(Cookie::new(SESSION_COOKIE, user.id.to_string()));
+
+    Ok(Ok(Flash::success(
+        Redirect::to("/"),
+        format!("Welcome, {}!", user.username),
+    )))
+}
+
+/// Settings of the accounts, read from the `[<profile>.auth]` table of `Rocket.toml`
+/// and `AUTH_*` environment variables (e.g. `AUTH_MANAGER_PASSWORD`).
+#[derive(Deserialize, Default)]
+#[serde(crate = "rocket::serde", default)]
+struct AuthConfig {
+    manager_username: Option<String>,
+    manager_password: Option<String>,
+}
+
+/// Creates the manager account of `config` unless a user with its name exists already,
+/// returning whether it is created
+async fn create_manager(db: &DatabaseConnection, config: AuthConfig) -> Result<bool, Error> {
+    let (Some(username), Some(password)) = (config.manager_username, config.manager_password)
+    else {
+        let has_manager = user::Entity::find()
+            .filter(user::Column::Role.eq(Role::Manager))
+            .one(db)
+            .await?
+            .is_some();
+        if !has_manager {
+            rocket::warn!(
+                "No manager account exists, set AUTH_MANAGER_USERNAME and AUTH_MANAGER_PASSWORD to create one."
+            );
+        }
+        return Ok(false);
+    };
+
+    if find_user(db, &username).await?.is_some() {
+        return Ok(false);
+    }
+
+    user::ActiveModel {
+        username: ActiveValue::Set(username),
+        password_hash: ActiveValue::Set(hash_password(password).await?),
+        role: ActiveValue::Set(Role::Manager),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    Ok(true)
+}
+
+/// Creates a fairing that mounts the account routes and creates the configured manager
+/// account on ignite. It must be attached after the migrations.
+pub(super) fn stage() -> AdHoc {
+    AdHoc::try_on_ignite("Accounts", |rocket| async move {
+        let config = match rocket
+            .figment()
+            .focus("auth")
+            .merge(Env::prefixed("AUTH_").global())
+            .extract::<AuthConfig>()
+        {
+            Ok(config) => config,
+            Err(err) => {
+                rocket::error!("Invalid auth configuration: {}", err);
+                return Err(rocket);
+            }
+        };
+        let Some(db) = rocket.state::<DatabaseConnection>() else {
+            rocket::error!("The database must be set up before the accounts.");
+            return Err(rocket);
+        };
+
+        let username = config.manager_username.clone();
+        match create_manager(db, config).await {
+            Ok(true) => {
+                rocket::info!(
+                    "Created the manager account {}",
+                    username.unwrap_or_default()
+                )
+            }
+            Ok(false) => {}
+            Err(err) => {
+                rocket::error!("Failed to create the manager account: {:?}", err);
+                return Err(rocket);
+            }
+        }
+
+        Ok(rocket.mount("/", routes![login, log_in, log_out, signup, sign_up]))
+    })
+}
@@ -0,0 +1,334 @@
+//! Web pages to manage chefs.
+
+use std::collections::HashMap;
+
+use bakery_core::{
+    entities::{prelude::*, *},
+    update_audited, ContactDetails, SoftDelete,
+};
+use rocket::{
+    form::{self, Context, Contextual, Form},
+    request::FlashMessage,
+    response::{Flash, Redirect},
+    *,
+};
+use rocket_dyn_templates::Template;
+use sea_orm::*;
+use serde_json::json;
+
+use super::{
+    auth::Manager,
+    error::Error,
+    forms::{self, field_errors},
+};
+
+#[derive(FromForm)]
+pub(super) struct ChefForm<'r> {
+    #[field(validate = forms::name())]
+    name: &'r str,
+    bakery_id: i32,
+    #[field(default = "")]
+    email: &'r str,
+    #[field(default = "")]
+    phone: &'r str,
+    #[field(default = "")]
+    address: &'r str,
+}
+
+impl ChefForm<'_> {
+    /// The contact details of the submitted fields, `None` when they are all empty
+    fn contact_details(&self) -> Option<ContactDetails> {
+        let field = |value: &str| Some(value.trim().to_owned()).filter(|value| !value.is_empty());
+        let details = ContactDetails {
+            email: field(self.email),
+            phone: field(self.phone),
+            address: field(self.address),
+        };
+
+        (!details.is_empty()).then_some(details)
+    }
+}
+
+async fn find_chef(db: &DatabaseConnection, id: i32) -> Result<chef::Model, Error> {
+    match Chef::find_active_by_id(id).one(db).await? {
+        Some(chef) => Ok(chef),
+        None => Err(Error::not_found(format!("No chef with id {id} is found."))),
+    }
+}
+
+/// Renders the form to create (without `id`) or edit a chef, filled with `values`
+async fn render_form(
+    db: &DatabaseConnection,
+    id: Option<i32>,
+    values: serde_json::Value,
+    errors: HashMap<String, String>,
+) -> Result<Template, Error> {
+    let bakeries = Bakery::find_active()
+        .order_by_asc(bakery::Column::Name)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|b| json!({ "id": b.id, "name": b.name }))
+        .collect::<Vec<_>>();
+
+    Ok(Template::render(
+        "chef_form",
+        json!({ "id": id, "values": values, "bakeries": bakeries, "errors": errors }),
+    ))
+}
+
+/// The submitted values and their errors, to render the form again
+fn submitted(context: &Context<'_>) -> (serde_json::Value, HashMap<String, String>) {
+    let values = json!({
+        "name": context.field_value("name"),
+        "bakery_id": context.field_value("bakery_id"),
+        "email": context.field_value("email"),
+        "phone": context.field_value("phone"),
+        "address": context.field_value("address"),
+    });
+
+    (values, field_errors(context))
+}
+
+/// Checks the submitted form, including that the chosen bakery exists and the contact details
+async fn validate<'r>(
+    db: &DatabaseConnection,
+    form: &mut Contextual<'r, ChefForm<'r>>,
+) -> Result<bool, Error> {
+    if let Some(value) = &form.value {
+        if Bakery::find_active_by_id(value.bakery_id)
+            .one(db)
+            .await?
+            .is_none()
+        {
+            form.context.push_error(
+                form::Error::validation("Please choose an existing bakery.").with_name("bakery_id"),
+            );
+        }
+        if let Err(err) = value
+            .contact_details()
+            .as_ref()
+            .map_or(Ok(()), ContactDetails::validate)
+        {
+            form.context
+                .push_error(form::Error::validation(err.message).with_name(err.field));
+        }
+    }
+
+    Ok(form.value.is_some() && form.context.errors().next().is_none())
+}
+
+#[get("/chefs")]
+async fn chefs(db: &State<DatabaseConnection>) -> Result<Template, Error> {
+    let db = db as &DatabaseConnection;
+
+    let chefs = Chef::find_active()
+        .find_also_related(Bakery)
+        .order_by_asc(chef::Column::Name)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|(c, b)| {
+            json!({
+                "id": c.id,
+                "name": c.name,
+                "bakery": b.map(|b| json!({ "id": b.id, "name": b.name })),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(Template::render(
+        "chefs",
+        json!({ "chefs": chefs, "num_chefs": chefs.len() }),
+    ))
+}
+
+#[get("/chefs/<id>")]
+async fn chef_by_id(db: &State<DatabaseConnection>, id: i32) -> Result<Template, Error> {
+    let db = db as &DatabaseConnection;
+
+    let chef = find_chef(db, id).await?;
+    let bakery = chef.find_related(Bakery).one(db).await?;
+
+    Ok(Template::render(
+        "chef",
+        json!({
+            "id": chef.id,
+            "name": chef.name,
+            "bakery": bakery.map(|b| json!({ "id": b.id, "name": b.name })),
+            "contact_details": chef.contact_details,
+            "created_at": chef.created_at,
+            "updated_at": chef.updated_at,
+        }),
+    ))
+}
+
+#[get("/chefs/new")]
+async fn new_chef(_manager: Manager, db: &State<DatabaseConnection>) -> Result<Template, Error> {
+    let db = db as &DatabaseConnection;
+
+    render_form(db, None, json!({}), HashMap::new()).await
+}
+
+#[post("/chefs", data = "<form>")]
+async fn create_chef<'r>(
+    _manager: Manager,
+    db: &State<DatabaseConnection>,
+    mut form: Form<Contextual<'r, ChefForm<'r>>>,
+) -> Result<Result<Redirect, Template>, Error> {
+    let db = db as &DatabaseConnection;
+
+    if !validate(db, &mut form).await? {
+        let (values, errors) = submitted(&form.context);
+        return Ok(Err(render_form(db, None, values, errors).await?));
+    }
+    let value = form.value.as_ref().unwrap();
+
+    let chef = chef::ActiveModel {
+        name: ActiveValue::Set(value.name.trim().to_owned()),
+        bakery_id: ActiveValue::Set(chef::bakery_id(value.bakery_id)),
+        contact_details: ActiveValue::Set(value.contact_details()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    Ok(Ok(Redirect::to(uri!(chef_by_id(chef.id)))))
+}
+
+#[get("/chefs/<id>/edit")]
+async fn edit_chef(
+    _manager: Manager,
+    db: &State<DatabaseConnection>,
+    id: i32,
+) -> Result<Template, Error> {
+    let db = db as &DatabaseConnection;
+
+    let chef = find_chef(db, id).await?;
+    let bakery_id = chef.works_at();
+    let contact_details = chef.contact_details.unwrap_or_default();
+    let values = json!({
+        "name": chef.name,
+        "bakery_id": bakery_id.map(|id| id.to_string()),
+        "email": contact_details.email,
+        "phone": contact_details.phone,
+        "address": contact_details.address,
+    });
+
+    render_form(db, Some(id), values, HashMap::new()).await
+}
+
+#[post("/chefs/<id>", data = "<form>")]
+async fn update_chef<'r>(
+    _manager: Manager,
+    db: &State<DatabaseConnection>,
+    id: i32,
+    mut form: Form<Contextual<'r, ChefForm<'r>>>,
+) -> Result<Result<Redirect, Template>, Error> {
+    let db = db as &DatabaseConnection;
+
+    let mut chef = find_chef(db, id).await?.into_active_model();
+
+    if !validate(db, &mut form).await? {
+        let (values, errors) = submitted(&form.context);
+        return Ok(Err(render_form(db, Some(id), values, errors).await?));
+    }
+    let value = form.value.as_ref().unwrap();
+
+    chef.name = ActiveValue::Set(value.name.trim().to_owned());
+    chef.bakery_id = ActiveValue::Set(chef::bakery_id(value.bakery_id));
+    chef.contact_details = ActiveValue::Set(value.contact_details());
+    update_audited(chef, db).await?;
+
+    Ok(Ok(Redirect::to(uri!(chef_by_id(id)))))
+}
+
+#[post("/chefs/<id>/delete")]
+async fn delete_chef(
+    _manager: Manager,
+    db: &State<DatabaseConnection>,
+    id: i32,
+) -> Result<Redirect, Error> {
+    let db = db as &DatabaseConnection;
+
+    Chef::soft_delete(find_chef(db, id).await?, db).await?;
+
+    Ok(Redirect::to(uri!(chefs)))
+}
+
+#[get("/chefs/deleted")]
+async fn deleted_chefs(
+    _manager: Manager,
+    db: &State<DatabaseConnection>,
+    flash: Option<FlashMessage<'_>>,
+) -> Result<Template, Error> {
+    let db = db as &DatabaseConnection;
+
+    let chefs = Chef::find_deleted()
+        .order_by_desc(chef::Column::DeletedAt)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|c| json!({ "id": c.id, "name": c.name, "deleted_at": c.deleted_at }))
+        .collect::<Vec<_>>();
+
+    Ok(Template::render(
+        "deleted",
+        json!({
+            "kind": "chefs",
+            "records": chefs,
+            "flash": flash.as_ref().map(|f| f.message().to_owned()),
+            "flash_kind": flash.map(|f| f.kind().to_owned()),
+        }),
+    ))
+}
+
+/// Restores a soft deleted chef, unless their bakery is soft deleted too
+#[post("/chefs/<id>/restore")]
+async fn restore_chef(
+    _manager: Manager,
+    db: &State<DatabaseConnection>,
+    id: i32,
+) -> Result<Result<Redirect, Flash<Redirect>>, Error> {
+    let db = db as &DatabaseConnection;
+
+    let Some(chef) = Chef::find_deleted()
+        .filter(chef::Column::Id.eq(id))
+        .one(db)
+        .await?
+    else {
+        return Err(Error::not_found(format!(
+            "No deleted chef with id {id} is found."
+        )));
+    };
+    let bakery_deleted = match chef.works_at() {
+        Some(bakery_id) => Bakery::find_active_by_id(bakery_id)
+            .one(db)
+            .await?
+            .is_none(),
+        None => false,
+    };
+    if bakery_deleted {
+        return Ok(Err(Flash::error(
+            Redirect::to(uri!(deleted_chefs)),
+            format!("The bakery of {} is deleted, restore it first.", chef.name),
+        )));
+    }
+    Chef::restore(chef, db).await?;
+
+    Ok(Ok(Redirect::to(uri!(chef_by_id(id)))))
+}
+
+pub(super) fn routes() -> Vec<Route> {
+    routes![
+        chefs,
+        chef_by_id,
+        new_chef,
+        create_chef,
+        edit_chef,
+        update_chef,
+        delete_chef,
+        deleted_chefs,
+        restore_chef
+    ]
+}
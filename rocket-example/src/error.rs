@@ -0,0 +1,183 @@
+//! Errors of the routes and the catchers, answered as JSON or as an HTML page depending on the client.
+
+use rocket::{
+    catch, catchers,
+    http::Status,
+    response::{self, Responder},
+    serde::json::Json,
+    Catcher, Request, Response,
+};
+use rocket_dyn_templates::Template;
+use sea_orm::{DbErr, SqlErr};
+use serde_json::{json, Value};
+
+#[derive(Debug)]
+pub(super) enum Error {
+    /// The request is malformed (400)
+    BadRequest(String),
+    /// The client is not logged in (401)
+    Unauthorized(String),
+    /// The client is not allowed to do this (403)
+    Forbidden(String),
+    /// The requested record does not exist (404)
+    NotFound(String),
+    /// The submitted data is invalid (422), `details` tells which part of it
+    Validation { message: String, details: Value },
+    /// The change violates a unique or foreign key constraint (409)
+    Conflict(String),
+    /// Anything else, whose cause is logged rather than sent to the client (500)
+    Internal,
+}
+
+impl Error {
+    pub(super) fn not_found(message: impl Into<String>) -> Self {
+        Self::NotFound(message.into())
+    }
+
+    /// A validation error of the field `field`
+    pub(super) fn invalid_field(field: &str, message: impl Into<String>) -> Self {
+        Self::Validation {
+            message: message.into(),
+            details: json!({ "field": field }),
+        }
+    }
+
+    pub(super) fn status(&self) -> Status {
+        match self {
+            Self::BadRequest(_) => Status::BadRequest,
+            Self::Unauthorized(_) => Status::Unauthorized,
+            Self::Forbidden(_) => Status::Forbidden,
+            Self::NotFound(_) => Status::NotFound,
+            Self::Validation { .. } => Status::UnprocessableEntity,
+            Self::Conflict(_) => Status::Conflict,
+            Self::Internal => Status::InternalServerError,
+        }
+    }
+
+    fn code(&self) -> &'static str {
+        match self {
+            Self::BadRequest(_) => "bad_request",
+            Self::Unauthorized(_) => "unauthorized",
+            Self::Forbidden(_) => "forbidden",
+            Self::NotFound(_) => "not_found",
+            Self::Validation { .. } => "validation_error",
+            Self::Conflict(_) => "conflict",
+            Self::Internal => "internal_error",
+        }
+    }
+
+    fn message(&self) -> &str {
+        match self {
+            Self::BadRequest(message)
+            | Self::Unauthorized(message)
+            | Self::Forbidden(message)
+            | Self::NotFound(message)
+            | Self::Conflict(message) => message,
+            Self::Validation { message, .. } => message,
+            Self::Internal => "Something went wrong on our side, please try again later.",
+        }
+    }
+
+    /// The `{ code, message, details }` body of the error
+    pub(super) fn to_json(&self) -> Value {
+        let details = match self {
+            Self::Validation { details, .. } => details.clone(),
+            _ => Value::Null,
+        };
+
+        json!({ "code": self.code(), "message": self.message(), "details": details })
+    }
+}
+
+impl From<DbErr> for Error {
+    fn from(err: DbErr) -> Error {
+        match err.sql_err() {
+            Some(SqlErr::UniqueConstraintViolation(cause)) => Self::Conflict(format!(
+                "A record with the same unique value already exists ({cause})."
+            )),
+            Some(SqlErr::ForeignKeyConstraintViolation(cause)) => Self::Conflict(format!(
+                "The change is refused by the related records ({cause})."
+            )),
+            _ => match err {
+                DbErr::RecordNotFound(message) => Self::NotFound(message),
+                err => {
+                    rocket::error!("Database error: {}", err);
+                    Self::Internal
+                }
+            },
+        }
+    }
+}
+
+impl<'r> Responder<'r, 'static> for Error {
+    fn respond_to(self, req: &'r Request<'_>) -> response::Result<'static> {
+        let status = self.status();
+        let body = self.to_json();
+
+        // Browsers ask for HTML first, while API clients accept anything or JSON
+        let prefers_html = req
+            .accept()
+            .is_some_and(|accept| accept.preferred().media_type().is_html());
+        let response = if prefers_html {
+            let template = match status.code {
+                400 | 401 | 403 | 404 | 422 | 500 => format!("error/{}", status.code),
+                _ => "error/error".to_owned(),
+            };
+            Template::render(
+                template,
+                json!({ "status": status.code, "reason": status.reason(), "error": body }),
+            )
+            .respond_to(req)?
+        } else {
+            Json(body).respond_to(req)?
+        };
+
+        Response::build_from(response).status(status).ok()
+    }
+}
+
+#[catch(400)]
+fn bad_request() -> Error {
+    Error::BadRequest("The request could not be understood.".to_owned())
+}
+
+#[catch(401)]
+fn unauthorized() -> Error {
+    Error::Unauthorized("Please log in first.".to_owned())
+}
+
+#[catch(403)]
+fn forbidden() -> Error {
+    Error::Forbidden("You are not allowed to do this.".to_owned())
+}
+
+#[catch(404)]
+fn not_found(req: &Request<'_>) -> Error {
+    Error::not_found(format!("The page at {} does not exist!", req.uri()))
+}
+
+#[catch(422)]
+fn unprocessable_entity() -> Error {
+    Error::Validation {
+        message: "The submitted data is invalid.".to_owned(),
+        details: Value::Null,
+    }
+}
+
+#[catch(500)]
+fn internal_error() -> Error {
+    Error::Internal
+}
+
+/// Catchers of the errors that are not answered by the routes themselves,
+/// responding like [`Error`] with JSON or an HTML page
+pub(super) fn catchers() -> Vec<Catcher> {
+    catchers![
+        bad_request,
+        unauthorized,
+        forbidden,
+        not_found,
+        unprocessable_entity,
+        internal_error
+    ]
+}
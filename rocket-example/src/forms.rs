@@ -0,0 +1,46 @@
+use std::collections::HashMap;
+
+use bakery_core::validate_profit_margin;
+use rocket::form::{self, Context};
+
+/// Collects the first error message of each field, to be rendered next to the fields of a form.
+pub(super) fn field_errors(context: &Context<'_>) -> HashMap<String, String> {
+    let mut errors = HashMap::new();
+    for error in context.errors() {
+        if let Some(name) = &error.name {
+            errors
+                .entry(name.to_string())
+                .or_insert_with(|| error.kind.to_string());
+        }
+    }
+    errors
+}
+
+/// Form validator of a name field, which must not be blank. The name is saved trimmed.
+pub(super) fn name<'v>(value: &str) -> form::Result<'v, ()> {
+    if value.trim().is_empty() {
+        return Err(form::Error::validation("Please enter a name.").into());
+    }
+    Ok(())
+}
+
+/// Parses the profit margin field of a bakery form, in percent, where an empty field means 0
+pub(super) fn parse_profit_margin(value: &str) -> Result<f64, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(0.0);
+    }
+
+    let margin = value
+        .parse::<f64>()
+        .map_err(|_| "The profit margin must be a number.".to_owned())?;
+    validate_profit_margin(margin).map_err(|err| err.to_string())?;
+    Ok(margin)
+}
+
+/// Form validator of the profit margin field, see [`parse_profit_margin`]
+pub(super) fn profit_margin<'v>(value: &str) -> form::Result<'v, ()> {
+    parse_profit_margin(value)
+        .map(|_| ())
+        .map_err(|message| form::Error::validation(message).into())
+}
@@ -1,147 +1,449 @@
-mod entities;
-mod migrator;
-mod setup;
+mod admin;
+mod api;
+mod audit;
+mod auth;
+mod chefs;
+mod error;
+mod forms;
 
-use entities::{prelude::*, *};
-use migrator::Migrator;
+use std::collections::HashMap;
+
+use auth::{Manager, User};
+use bakery_core::{
+    db_config,
+    entities::{prelude::*, *},
+    run_migrations, set_up_db, update_audited, SoftDelete,
+};
+use error::Error;
+use forms::field_errors;
 use rocket::{
+    form::{self, Contextual, Form},
     fs::{relative, FileServer},
+    request::FlashMessage,
+    response::{Flash, Redirect},
     *,
 };
 use rocket_dyn_templates::Template;
 use sea_orm::*;
-use sea_orm_migration::MigratorTrait;
 use serde_json::json;
-use setup::set_up_db;
 
 #[get("/")]
-fn index() -> Template {
-    Template::render("index", json!({}))
+fn index(user: Option<User>, flash: Option<FlashMessage<'_>>) -> Template {
+    Template::render(
+        "index",
+        json!({
+            "user": user.map(|u| json!({ "username": u.username, "role": u.role })),
+            "flash": flash.map(|f| f.message().to_owned()),
+        }),
+    )
+}
+
+const DEFAULT_BAKERIES_PER_PAGE: u64 = 10;
+const MAX_BAKERIES_PER_PAGE: u64 = 100;
+
+/// The page to show out of `num_pages`, numbered from 1 as in the web page: a missing
+/// or zero page is the first one, and one past the end is the last one
+fn clamp_page(page: Option<u64>, num_pages: u64) -> u64 {
+    page.unwrap_or(1).clamp(1, num_pages.max(1))
+}
+
+/// Orderings of the bakery listing, given by the `sort` query parameter
+#[derive(FromFormField, Clone, Copy, Default)]
+enum BakerySort {
+    #[default]
+    #[field(value = "id")]
+    Id,
+    #[field(value = "name")]
+    Name,
+    #[field(value = "-name")]
+    NameDesc,
+    #[field(value = "profit_margin")]
+    ProfitMargin,
+    #[field(value = "-profit_margin")]
+    ProfitMarginDesc,
+}
+
+impl BakerySort {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Id => "id",
+            Self::Name => "name",
+            Self::NameDesc => "-name",
+            Self::ProfitMargin => "profit_margin",
+            Self::ProfitMarginDesc => "-profit_margin",
+        }
+    }
+
+    fn apply(self, select: Select<Bakery>) -> Select<Bakery> {
+        match self {
+            Self::Id => select.order_by_asc(bakery::Column::Id),
+            Self::Name => select.order_by_asc(bakery::Column::Name),
+            Self::NameDesc => select.order_by_desc(bakery::Column::Name),
+            Self::ProfitMargin => select.order_by_asc(bakery::Column::ProfitMargin),
+            Self::ProfitMarginDesc => select.order_by_desc(bakery::Column::ProfitMargin),
+        }
+    }
 }
 
-#[get("/bakeries")]
-async fn bakeries(db: &State<DatabaseConnection>) -> Result<Template, ErrorResponder> {
+#[get("/bakeries?<page>&<per_page>&<sort>&<q>")]
+async fn bakeries(
+    db: &State<DatabaseConnection>,
+    flash: Option<FlashMessage<'_>>,
+    page: Option<u64>,
+    per_page: Option<u64>,
+    sort: Option<BakerySort>,
+    q: Option<&str>,
+) -> Result<Template, Error> {
     let db = db as &DatabaseConnection;
 
-    let bakeries = Bakery::find()
-        .all(db)
+    let per_page = per_page
+        .unwrap_or(DEFAULT_BAKERIES_PER_PAGE)
+        .clamp(1, MAX_BAKERIES_PER_PAGE);
+    let sort = sort.unwrap_or_default();
+    let q = q.map(str::trim).unwrap_or_default();
+
+    let mut select = Bakery::find_active();
+    if !q.is_empty() {
+        select = select.filter(bakery::Column::Name.contains(q));
+    }
+    let paginator = sort.apply(select).paginate(db, per_page);
+    let ItemsAndPagesNumber {
+        number_of_items,
+        number_of_pages,
+    } = paginator.num_items_and_pages().await?;
+    let page = clamp_page(page, number_of_pages);
+
+    // Pages are numbered from 1 in the web page, but from 0 by the `Paginator`
+    let bakeries = paginator
+        .fetch_page(page - 1)
         .await?
         .into_iter()
-        .map(|b| json!({ "name": b.name, "id": b.id }))
+        .map(|b| json!({ "name": b.name, "id": b.id, "profit_margin": b.profit_margin }))
         .collect::<Vec<_>>();
 
     Ok(Template::render(
         "bakeries",
-        json!({ "bakeries": bakeries, "num_bakeries": bakeries.len() }),
+        json!({
+            "bakeries": bakeries,
+            "num_bakeries": number_of_items,
+            "page": page,
+            "num_pages": number_of_pages,
+            "per_page": per_page,
+            "sort": sort.as_str(),
+            "q": q,
+            "flash": flash.map(|f| f.message().to_owned()),
+        }),
     ))
 }
 
 #[get("/bakeries/<id>")]
-async fn bakery_by_id(db: &State<DatabaseConnection>, id: i32) -> Result<Template, ErrorResponder> {
+async fn bakery_by_id(
+    db: &State<DatabaseConnection>,
+    id: i32,
+    flash: Option<FlashMessage<'_>>,
+) -> Result<Template, Error> {
     let db = db as &DatabaseConnection;
 
-    let bakery = Bakery::find_by_id(id).one(db).await?;
+    let bakery = Bakery::find_active_by_id(id).one(db).await?;
 
     Ok(if let Some(bakery) = bakery {
+        let chefs = bakery
+            .find_related(Chef)
+            .filter(chef::Column::DeletedAt.is_null())
+            .order_by_asc(chef::Column::Name)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|c| json!({ "name": c.name, "id": c.id }))
+            .collect::<Vec<_>>();
+
         Template::render(
             "bakery",
-            json!({ "id": bakery.id, "name": bakery.name, "profit_margin": bakery.profit_margin }),
+            json!({
+                "id": bakery.id,
+                "name": bakery.name,
+                "profit_margin": bakery.profit_margin,
+                "created_at": bakery.created_at,
+                "updated_at": bakery.updated_at,
+                "chefs": chefs,
+                "num_chefs": chefs.len(),
+                "flash": flash.map(|f| f.message().to_owned()),
+            }),
         )
     } else {
-        return Err(format!("No bakery with id {id} is found.").into());
+        return Err(Error::not_found(format!(
+            "No bakery with id {id} is found."
+        )));
     })
 }
 
+#[derive(FromForm)]
+struct BakeryForm<'r> {
+    #[field(validate = forms::name())]
+    name: &'r str,
+    #[field(default = "", validate = forms::profit_margin())]
+    profit_margin: &'r str,
+}
+
+/// Renders the form to create (without `id`) or edit a bakery, filled with `values`
+fn bakery_form(
+    id: Option<i32>,
+    values: serde_json::Value,
+    errors: HashMap<String, String>,
+) -> Template {
+    Template::render(
+        "bakery_form",
+        json!({ "id": id, "values": values, "errors": errors }),
+    )
+}
+
+/// Renders the form again with the submitted values and their errors
+fn invalid_bakery_form(id: Option<i32>, context: &form::Context<'_>) -> Template {
+    let values = json!({
+        "name": context.field_value("name"),
+        "profit_margin": context.field_value("profit_margin"),
+    });
+
+    bakery_form(id, values, field_errors(context))
+}
+
+/// Parses the profit margin of a submitted [`BakeryForm`]
+fn parse_profit_margin(value: &str) -> Result<f64, Error> {
+    forms::parse_profit_margin(value)
+        .map_err(|message| Error::invalid_field("profit_margin", message))
+}
+
+async fn find_bakery(db: &DatabaseConnection, id: i32) -> Result<bakery::Model, Error> {
+    match Bakery::find_active_by_id(id).one(db).await? {
+        Some(bakery) => Ok(bakery),
+        None => Err(Error::not_found(format!(
+            "No bakery with id {id} is found."
+        ))),
+    }
+}
+
 #[get("/new")]
-fn new() -> Template {
-    Template::render("new", json!({}))
+fn new(_manager: Manager) -> Template {
+    bakery_form(None, json!({}), HashMap::new())
 }
 
-// Use `GET` to support query parameters to simplify things
-#[get("/bakeries?<name>&<profit_margin>")]
-async fn new_bakery(
+#[post("/bakeries", data = "<form>")]
+async fn new_bakery<'r>(
+    _manager: Manager,
     db: &State<DatabaseConnection>,
-    name: &str,
-    profit_margin: Option<f64>,
-) -> Result<Template, ErrorResponder> {
+    form: Form<Contextual<'r, BakeryForm<'r>>>,
+) -> Result<Result<Flash<Redirect>, Template>, Error> {
     let db = db as &DatabaseConnection;
 
-    let profit_margin = profit_margin.unwrap_or_default();
+    let Some(value) = &form.value else {
+        return Ok(Err(invalid_bakery_form(None, &form.context)));
+    };
 
-    let new_bakery = bakery::ActiveModel {
-        name: ActiveValue::Set(name.to_owned()),
-        profit_margin: ActiveValue::Set(profit_margin),
+    let bakery = bakery::ActiveModel {
+        name: ActiveValue::Set(value.name.trim().to_owned()),
+        profit_margin: ActiveValue::Set(parse_profit_margin(value.profit_margin)?),
         ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    Ok(Ok(Flash::success(
+        Redirect::to(uri!(bakery_by_id(bakery.id))),
+        format!("{} is successfully registered!", bakery.name),
+    )))
+}
+
+#[get("/bakeries/<id>/edit")]
+async fn edit_bakery(
+    _manager: Manager,
+    db: &State<DatabaseConnection>,
+    id: i32,
+) -> Result<Template, Error> {
+    let db = db as &DatabaseConnection;
+
+    let bakery = find_bakery(db, id).await?;
+    let values = json!({
+        "name": bakery.name,
+        "profit_margin": bakery.profit_margin.to_string(),
+    });
+
+    Ok(bakery_form(Some(id), values, HashMap::new()))
+}
+
+#[post("/bakeries/<id>", data = "<form>")]
+async fn update_bakery<'r>(
+    _manager: Manager,
+    db: &State<DatabaseConnection>,
+    id: i32,
+    form: Form<Contextual<'r, BakeryForm<'r>>>,
+) -> Result<Result<Flash<Redirect>, Template>, Error> {
+    let db = db as &DatabaseConnection;
+
+    let mut bakery = find_bakery(db, id).await?.into_active_model();
+
+    let Some(value) = &form.value else {
+        return Ok(Err(invalid_bakery_form(Some(id), &form.context)));
     };
 
-    Bakery::insert(new_bakery)
-        .exec(db)
+    bakery.name = ActiveValue::Set(value.name.trim().to_owned());
+    bakery.profit_margin = ActiveValue::Set(parse_profit_margin(value.profit_margin)?);
+    let bakery = update_audited(bakery, db).await?;
+
+    Ok(Ok(Flash::success(
+        Redirect::to(uri!(bakery_by_id(id))),
+        format!("{} is successfully updated!", bakery.name),
+    )))
+}
+
+/// Renders the confirmation page of deleting a bakery, telling how many chefs are deleted with it
+#[get("/bakeries/<id>/delete")]
+async fn delete_bakery(
+    _manager: Manager,
+    db: &State<DatabaseConnection>,
+    id: i32,
+) -> Result<Template, Error> {
+    let db = db as &DatabaseConnection;
+
+    let bakery = find_bakery(db, id).await?;
+    let num_chefs = bakery
+        .find_related(Chef)
+        .filter(chef::Column::DeletedAt.is_null())
+        .count(db)
         .await?;
 
     Ok(Template::render(
-        "success",
-        json!({ "name": name, "profit_margin": profit_margin}),
+        "delete_bakery",
+        json!({ "id": bakery.id, "name": bakery.name, "num_chefs": num_chefs }),
+    ))
+}
+
+/// Soft deletes a bakery with its chefs, which can be restored from the deleted bakeries
+#[post("/bakeries/<id>/delete")]
+async fn confirm_delete_bakery(
+    _manager: Manager,
+    db: &State<DatabaseConnection>,
+    id: i32,
+) -> Result<Flash<Redirect>, Error> {
+    let db = db as &DatabaseConnection;
+
+    let bakery = Bakery::soft_delete(find_bakery(db, id).await?, db).await?;
+
+    Ok(Flash::success(
+        Redirect::to(uri!(bakeries(_, _, _, _))),
+        format!("{} is successfully deleted!", bakery.name),
+    ))
+}
+
+#[get("/bakeries/deleted")]
+async fn deleted_bakeries(
+    _manager: Manager,
+    db: &State<DatabaseConnection>,
+    flash: Option<FlashMessage<'_>>,
+) -> Result<Template, Error> {
+    let db = db as &DatabaseConnection;
+
+    let bakeries = Bakery::find_deleted()
+        .order_by_desc(bakery::Column::DeletedAt)
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|b| json!({ "id": b.id, "name": b.name, "deleted_at": b.deleted_at }))
+        .collect::<Vec<_>>();
+
+    Ok(Template::render(
+        "deleted",
+        json!({
+            "kind": "bakeries",
+            "records": bakeries,
+            "flash": flash.as_ref().map(|f| f.message().to_owned()),
+            "flash_kind": flash.map(|f| f.kind().to_owned()),
+        }),
     ))
 }
 
-#[post("/reset")]
-async fn reset(db: &State<DatabaseConnection>) -> Result<(), ErrorResponder> {
+/// Restores a soft deleted bakery and the chefs deleted with it
+#[post("/bakeries/<id>/restore")]
+async fn restore_bakery(
+    _manager: Manager,
+    db: &State<DatabaseConnection>,
+    id: i32,
+) -> Result<Flash<Redirect>, Error> {
     let db = db as &DatabaseConnection;
-    Migrator::refresh(db).await?;
 
-    Ok(())
+    let Some(bakery) = Bakery::find_deleted()
+        .filter(bakery::Column::Id.eq(id))
+        .one(db)
+        .await?
+    else {
+        return Err(Error::not_found(format!(
+            "No deleted bakery with id {id} is found."
+        )));
+    };
+    let bakery = Bakery::restore(bakery, db).await?;
+
+    Ok(Flash::success(
+        Redirect::to(uri!(bakery_by_id(id))),
+        format!("{} is successfully restored!", bakery.name),
+    ))
 }
 
 #[launch]
 async fn rocket() -> _ {
-    let db = match set_up_db().await {
+    let db_config = match db_config(&Config::figment()) {
+        Ok(config) => config,
+        Err(err) => panic!("{}", err),
+    };
+    let db = match set_up_db(&db_config).await {
         Ok(db) => db,
         Err(err) => panic!("{}", err),
     };
 
     rocket::build()
-        .manage(db)
+        .attach(run_migrations(db, |rocket, db| rocket.manage(db)))
         .mount("/", FileServer::from(relative!("/static")))
         .mount(
             "/",
-            routes![index, bakeries, bakery_by_id, new, new_bakery, reset],
+            routes![
+                index,
+                bakeries,
+                bakery_by_id,
+                new,
+                new_bakery,
+                edit_bakery,
+                update_bakery,
+                delete_bakery,
+                confirm_delete_bakery,
+                deleted_bakeries,
+                restore_bakery
+            ],
         )
-        .register("/", catchers![not_found])
+        .attach(admin::stage())
+        .attach(auth::stage())
+        .mount("/", chefs::routes())
+        .mount("/", audit::routes())
+        .mount("/api", api::routes())
+        .register("/", error::catchers())
         .attach(Template::fairing())
 }
 
-#[catch(404)]
-pub fn not_found(req: &Request<'_>) -> Template {
-    Template::render(
-        "error/404",
-        json! ({
-            "uri": req.uri()
-        }),
-    )
-}
-
-#[derive(Responder)]
-#[response(status = 500, content_type = "json")]
-struct ErrorResponder {
-    message: String,
-}
+#[cfg(test)]
+mod tests {
+    use super::clamp_page;
 
-impl From<DbErr> for ErrorResponder {
-    fn from(err: DbErr) -> ErrorResponder {
-        ErrorResponder {
-            message: err.to_string(),
-        }
+    #[test]
+    fn page_zero_is_the_first_page() {
+        assert_eq!(clamp_page(Some(0), 5), 1);
+        assert_eq!(clamp_page(None, 5), 1);
     }
-}
 
-impl From<String> for ErrorResponder {
-    fn from(string: String) -> ErrorResponder {
-        ErrorResponder { message: string }
+    #[test]
+    fn page_out_of_range_is_the_last_page() {
+        assert_eq!(clamp_page(Some(6), 5), 5);
+        assert_eq!(clamp_page(Some(u64::MAX), 5), 5);
     }
-}
 
-impl From<&str> for ErrorResponder {
-    fn from(str: &str) -> ErrorResponder {
-        str.to_owned().into()
+    #[test]
+    fn no_records_have_one_empty_page() {
+        assert_eq!(clamp_page(Some(3), 0), 1);
     }
 }